@@ -0,0 +1,469 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::mem;
+use std::time::Duration;
+
+use crate::gesture::Point;
+use crate::keyboard::KeyState;
+use crate::linux::input_event_codes::*;
+use crate::mt::MtState;
+use crate::{gen, Error, Packet};
+
+/// Contacts that move less than this many device units are holding still.
+const SLOP: f64 = 30.0;
+/// Contacts held still for at least this long are long presses rather than taps.
+const LONG_PRESS: Duration = Duration::from_millis(500);
+/// Two fingers that turn by at least this many degrees rotate rather than pinch.
+const MIN_ROTATION: f64 = 15.0;
+
+/// A contact from touch down to lift off, with the positions it reported.
+#[derive(Debug)]
+struct Trace {
+    tracking_id: i32,
+    points: Vec<(Duration, Point)>,
+    end: Option<Duration>,
+}
+
+impl Trace {
+    fn start(&self) -> Duration {
+        self.points[0].0
+    }
+
+    /// Position at `time`, which is the last one reported up to then.
+    fn at(&self, time: Duration) -> Point {
+        let index = self.points.partition_point(|&(t, _)| t <= time);
+        self.points[index.saturating_sub(1)].1
+    }
+
+    /// Largest distance from the first position.
+    fn travel(&self) -> f64 {
+        let first = self.points[0].1;
+        self.points
+            .iter()
+            .map(|&(_, point)| distance(first, point))
+            .fold(0.0, f64::max)
+    }
+}
+
+/// State of one device while decompiling.
+#[derive(Debug, Default)]
+struct DeviceState {
+    mt: MtState,
+    /// Index of the trace of the contact in each slot.
+    open: BTreeMap<i32, usize>,
+    /// Contacts of the current gesture, which lasts while any of them touches.
+    traces: Vec<Trace>,
+    keys: KeyState,
+    /// Times at which the keys that are down were pressed.
+    pressed: HashMap<u16, Duration>,
+}
+
+impl DeviceState {
+    /// Updates the keys with an event. Returns a key press and its start when the key is
+    /// released.
+    fn event(
+        &mut self,
+        r#type: i32,
+        code: i32,
+        value: i32,
+        time: Duration,
+    ) -> Option<(Duration, String)> {
+        match (r#type, code) {
+            // `BTN_TOUCH` and `BTN_TOOL_*` accompany contacts.
+            (EV_KEY, BTN_DIGI..=BTN_TOOL_QUADTAP) => {}
+            (EV_KEY, _) => {
+                let code = code as u16;
+                if !self.keys.event(EV_KEY as u16, code, value) {
+                    return None;
+                }
+                if value != 0 {
+                    self.pressed.insert(code, time);
+                    return None;
+                }
+                let pressed = self.pressed.remove(&code)?;
+                let command = format!("key {} {}", key_name(code), format_duration(time - pressed));
+                return Some((pressed, command));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Updates the traces with the contacts at the end of a packet. Returns the gesture and its
+    /// start if the last contact lifted.
+    fn update(&mut self, time: Duration) -> Option<(Duration, String)> {
+        let contacts = self.mt.contacts();
+        let traces = &mut self.traces;
+        self.open.retain(|slot, &mut index| {
+            let trace = &mut traces[index];
+            match contacts.iter().find(|touch| touch.slot == *slot) {
+                Some(touch) if touch.tracking_id == trace.tracking_id => true,
+                _ => {
+                    trace.end = Some(time);
+                    false
+                }
+            }
+        });
+        for touch in contacts {
+            let point = Point {
+                x: touch.x.into(),
+                y: touch.y.into(),
+            };
+            match self.open.get(&touch.slot) {
+                Some(&index) => {
+                    let trace = &mut self.traces[index];
+                    if trace.points.last().unwrap().1 != point {
+                        trace.points.push((time, point));
+                    }
+                }
+                None => {
+                    self.open.insert(touch.slot, self.traces.len());
+                    self.traces.push(Trace {
+                        tracking_id: touch.tracking_id,
+                        points: vec![(time, point)],
+                        end: None,
+                    });
+                }
+            }
+        }
+        if !self.traces.is_empty() && self.traces.iter().all(|trace| trace.end.is_some()) {
+            Some(gesture(&mem::take(&mut self.traces)))
+        } else {
+            None
+        }
+    }
+
+    /// Describes what is still going on at the end of the recording.
+    fn finish(&mut self, time: Duration) -> Vec<(Duration, String)> {
+        let mut result = vec![];
+        if !self.traces.is_empty() {
+            for trace in &mut self.traces {
+                trace.end.get_or_insert(time);
+            }
+            let (start, command) = gesture(&self.traces);
+            let comment = "# still touching at the end of the recording".to_string();
+            result.push((start, comment));
+            result.push((start, command));
+        }
+        for code in self.keys.pressed() {
+            let comment = format!("# {} pressed and not released", key_name(code));
+            result.push((self.pressed[&code], comment));
+        }
+        result
+    }
+}
+
+/// Summarizes the contacts of a gesture as a script command, or as a comment if no command
+/// reproduces it. Returns the start of the gesture too.
+fn gesture(traces: &[Trace]) -> (Duration, String) {
+    let start = traces.iter().map(Trace::start).min().unwrap();
+    (start, command(traces, start))
+}
+
+fn command(traces: &[Trace], start: Duration) -> String {
+    let end = traces.iter().filter_map(|trace| trace.end).max().unwrap();
+    let duration = format_duration(end - start);
+    match traces {
+        [trace] => {
+            let from = trace.points[0].1;
+            if trace.travel() >= SLOP {
+                let to = trace.points.last().unwrap().1;
+                return format!("swipe {} {} {} {} {}", from.x, from.y, to.x, to.y, duration);
+            }
+            let command = if end - start >= LONG_PRESS {
+                "long-press"
+            } else {
+                "tap"
+            };
+            return format!("{} {} {} {}", command, from.x, from.y, duration);
+        }
+        [first, second] => {
+            // Compare the fingers while both touch.
+            let both = (
+                first.start().max(second.start()),
+                first.end.unwrap().min(second.end.unwrap()),
+            );
+            let at = |time| (first.at(time), second.at(time));
+            let ((a0, b0), (a1, b1)) = (at(both.0), at(both.1));
+            let center = Point {
+                x: ((a0.x + b0.x) / 2.0).round(),
+                y: ((a0.y + b0.y) / 2.0).round(),
+            };
+            let (from, to) = (distance(a0, b0), distance(a1, b1));
+            let angle = (a0.y - b0.y).atan2(a0.x - b0.x).to_degrees();
+            let turn =
+                ((a1.y - b1.y).atan2(a1.x - b1.x).to_degrees() - angle + 540.0) % 360.0 - 180.0;
+            if turn.abs() >= MIN_ROTATION {
+                return format!(
+                    "rotate {} {} {} {} {} {}",
+                    center.x,
+                    center.y,
+                    ((from + to) / 4.0).round(),
+                    angle.round(),
+                    (angle + turn).round(),
+                    duration
+                );
+            }
+            if (to - from).abs() >= SLOP {
+                return format!(
+                    "pinch {} {} {} {} {}",
+                    center.x,
+                    center.y,
+                    from.round(),
+                    to.round(),
+                    duration
+                );
+            }
+        }
+        _ => {}
+    }
+    let paths: Vec<_> = traces
+        .iter()
+        .map(|trace| {
+            let (from, to) = (trace.points[0].1, trace.points.last().unwrap().1);
+            format!("{} {} -> {} {}", from.x, from.y, to.x, to.y)
+        })
+        .collect();
+    format!(
+        "# {}-finger gesture for {}: {}",
+        traces.len(),
+        duration,
+        paths.join(", ")
+    )
+}
+
+fn distance(a: Point, b: Point) -> f64 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+fn key_name(code: u16) -> String {
+    gen::get_code_name(EV_KEY as u16, code)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{:04x}", code))
+}
+
+/// Formats a duration in the largest of `ms` and `us` that represents it exactly.
+fn format_duration(duration: Duration) -> String {
+    if duration.subsec_micros().is_multiple_of(1000) {
+        format!("{}ms", duration.as_millis())
+    } else {
+        format!("{}us", duration.as_micros())
+    }
+}
+
+/// Writes a script of the taps, swipes, long presses, pinches, rotations and key presses in
+/// the packets. Each action waits until its time relative to the first packet. Gestures that no
+/// script command reproduces are described in comments.
+pub(crate) fn decompile(
+    packets: impl Iterator<Item = Result<Packet, Error>>,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    let mut devices: HashMap<String, DeviceState> = HashMap::new();
+    // Time, device and command of each action.
+    let mut actions: Vec<(Duration, String, String)> = vec![];
+    let mut start = None;
+    let mut end = Duration::ZERO;
+
+    for result in packets {
+        let packet = result?;
+        let start = *start.get_or_insert(packet.time());
+        let time = packet.time().saturating_sub(start);
+        end = end.max(time);
+        let state = devices.entry(packet.device.clone()).or_default();
+        state.mt.packet(&packet);
+        for event in &packet.events {
+            let (r#type, code) = (i32::from(event.r#type), i32::from(event.code));
+            if let Some((time, command)) = state.event(r#type, code, event.value, time) {
+                actions.push((time, packet.device.clone(), command));
+            }
+        }
+        if let Some((time, command)) = state.update(time) {
+            actions.push((time, packet.device.clone(), command));
+        }
+    }
+    for (device, state) in &mut devices {
+        for (time, command) in state.finish(end) {
+            actions.push((time, device.clone(), command));
+        }
+    }
+    // Stable, so that simultaneous actions stay in the order in which they ended.
+    actions.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+
+    let mut device = None;
+    let mut waited = Duration::ZERO;
+    for (time, action_device, command) in actions {
+        if device.as_ref() != Some(&action_device) {
+            writeln!(output, "device {}", action_device)?;
+            device = Some(action_device);
+        }
+        if time > waited {
+            writeln!(output, "wait {}", format_duration(time))?;
+            waited = time;
+        }
+        writeln!(output, "{}", command)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decompile;
+    use crate::linux::input_event_codes::*;
+    use crate::Packet;
+    use std::time::Duration;
+
+    const TOUCH: &str = "/dev/input/event2";
+    const KEYS: &str = "/dev/input/event0";
+
+    fn run(packets: Vec<Packet>) -> String {
+        let mut output = vec![];
+        decompile(packets.into_iter().map(Ok), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    fn packet(device: &str, millis: u64, events: &[(i32, i32, i32)]) -> Packet {
+        Packet::new(
+            device,
+            Duration::from_millis(1000 + millis),
+            events.to_vec(),
+        )
+    }
+
+    #[test]
+    fn touches_and_keys() {
+        let packets = vec![
+            packet(
+                TOUCH,
+                0,
+                &[
+                    (EV_ABS, ABS_MT_TRACKING_ID, 1),
+                    (EV_ABS, ABS_MT_POSITION_X, 540),
+                    (EV_ABS, ABS_MT_POSITION_Y, 1200),
+                    (EV_KEY, BTN_TOUCH, 1),
+                ],
+            ),
+            packet(TOUCH, 80, &[(EV_ABS, ABS_MT_POSITION_X, 545)]),
+            packet(
+                TOUCH,
+                100,
+                &[(EV_ABS, ABS_MT_TRACKING_ID, -1), (EV_KEY, BTN_TOUCH, 0)],
+            ),
+            packet(KEYS, 150, &[(EV_KEY, KEY_POWER, 1)]),
+            // The swipe starts in the same slot, at the position of the tap.
+            packet(TOUCH, 200, &[(EV_ABS, ABS_MT_TRACKING_ID, 2)]),
+            packet(KEYS, 250, &[(EV_KEY, KEY_POWER, 0)]),
+            packet(TOUCH, 300, &[(EV_ABS, ABS_MT_POSITION_Y, 800)]),
+            packet(TOUCH, 350, &[(EV_ABS, ABS_MT_TRACKING_ID, -1)]),
+            packet(KEYS, 400, &[(EV_KEY, KEY_A, 1)]),
+        ];
+        assert_eq!(
+            run(packets),
+            "\
+device /dev/input/event2
+tap 540 1200 100ms
+device /dev/input/event0
+wait 150ms
+key KEY_POWER 100ms
+device /dev/input/event2
+wait 200ms
+swipe 545 1200 545 800 150ms
+device /dev/input/event0
+wait 400ms
+# KEY_A pressed and not released
+"
+        );
+    }
+
+    #[test]
+    fn multitouch() {
+        let finger = |slot, id, x, y| {
+            vec![
+                (EV_ABS, ABS_MT_SLOT, slot),
+                (EV_ABS, ABS_MT_TRACKING_ID, id),
+                (EV_ABS, ABS_MT_POSITION_X, x),
+                (EV_ABS, ABS_MT_POSITION_Y, y),
+            ]
+        };
+        let lift = |slot| {
+            [
+                (EV_ABS, ABS_MT_SLOT, slot),
+                (EV_ABS, ABS_MT_TRACKING_ID, -1),
+            ]
+        };
+        let packets = vec![
+            // Fingers 200 apart that move 600 apart.
+            packet(
+                TOUCH,
+                0,
+                &[finger(0, 1, 440, 1200), finger(1, 2, 640, 1200)].concat(),
+            ),
+            packet(
+                TOUCH,
+                500,
+                &[finger(0, 1, 240, 1200), finger(1, 2, 840, 1200)].concat(),
+            ),
+            packet(TOUCH, 500, &[lift(0), lift(1)].concat()),
+            // Fingers that turn by 90 degrees.
+            packet(
+                TOUCH,
+                1000,
+                &[finger(0, 3, 840, 1200), finger(1, 4, 240, 1200)].concat(),
+            ),
+            packet(
+                TOUCH,
+                1500,
+                &[finger(0, 3, 540, 1500), finger(1, 4, 540, 900)].concat(),
+            ),
+            packet(TOUCH, 1500, &[lift(0), lift(1)].concat()),
+            // Three fingers.
+            packet(
+                TOUCH,
+                2000,
+                &[
+                    finger(0, 5, 100, 100),
+                    finger(1, 6, 200, 100),
+                    finger(2, 7, 300, 100),
+                ]
+                .concat(),
+            ),
+            packet(TOUCH, 2100, &[lift(0), lift(1), lift(2)].concat()),
+            // Still touching at the end.
+            packet(TOUCH, 3000, &finger(0, 8, 100, 100)),
+            packet(TOUCH, 4000, &[(EV_ABS, ABS_MT_POSITION_Y, 200)]),
+        ];
+        assert_eq!(
+            run(packets),
+            "\
+device /dev/input/event2
+pinch 540 1200 200 600 500ms
+wait 1000ms
+rotate 540 1200 300 0 90 500ms
+wait 2000ms
+# 3-finger gesture for 100ms: 100 100 -> 100 100, 200 100 -> 200 100, 300 100 -> 300 100
+wait 3000ms
+# still touching at the end of the recording
+swipe 100 100 100 200 1000ms
+"
+        );
+    }
+
+    #[test]
+    fn single_touch() {
+        let packets = vec![
+            packet(
+                TOUCH,
+                0,
+                &[
+                    (EV_KEY, BTN_TOUCH, 1),
+                    (EV_ABS, ABS_X, 10),
+                    (EV_ABS, ABS_Y, 20),
+                ],
+            ),
+            packet(TOUCH, 600, &[(EV_KEY, BTN_TOUCH, 0)]),
+        ];
+        assert_eq!(
+            run(packets),
+            "device /dev/input/event2\nlong-press 10 20 600ms\n"
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{self, BufRead};
+use std::os::unix::io::{AsRawFd, RawFd};
+
+use crate::linux::input::{self, input_absinfo};
+use crate::linux::input_event_codes;
+use crate::{gen, Error, Radix};
+
+/// Capabilities of a device that event generators need to know about.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// Ranges of the supported `EV_ABS` codes.
+    pub abs: BTreeMap<u16, input_absinfo>,
+    /// Supported `EV_KEY` codes.
+    pub keys: BTreeSet<u16>,
+    /// `INPUT_PROP_*` properties, such as `INPUT_PROP_DIRECT` for touchscreens.
+    pub props: BTreeSet<u16>,
+}
+
+impl DeviceInfo {
+    /// Queries the capabilities of a device with the `EVIOCGBIT` and `EVIOCGABS` ioctls.
+    pub fn query(path: &str) -> Result<Self, Error> {
+        let file = File::open(path)?;
+        let fd = file.as_raw_fd();
+        let mut info = DeviceInfo::default();
+        for code in bits(fd, input_event_codes::EV_ABS, input_event_codes::ABS_MAX)? {
+            let mut absinfo = input_absinfo::default();
+            if unsafe { libc::ioctl(fd, input::EVIOCGABS(code.into()) as _, &mut absinfo) } < 0 {
+                return Err(io::Error::last_os_error().into());
+            }
+            info.abs.insert(code, absinfo);
+        }
+        info.keys = bits(fd, input_event_codes::EV_KEY, input_event_codes::KEY_MAX)?
+            .into_iter()
+            .collect();
+        let mut buffer = [0u8; input_event_codes::INPUT_PROP_CNT as usize / 8];
+        let request = input::EVIOCGPROP(buffer.len() as u32);
+        if unsafe { libc::ioctl(fd, request as _, buffer.as_mut_ptr()) } < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        info.props = set_bits(&buffer, input_event_codes::INPUT_PROP_MAX);
+        Ok(info)
+    }
+
+    /// Reads the capabilities of `device` from the output of `getevent -p`, with or without
+    /// `-l`. The sections of other devices are skipped.
+    pub fn from_getevent(
+        reader: &mut (impl BufRead + ?Sized),
+        device: &str,
+    ) -> Result<Self, Error> {
+        Self::parse_getevent(reader, Some(device))?
+            .remove(device)
+            .ok_or_else(|| Error::Format(format!("device {:?} not found", device)))
+    }
+
+    /// Reads the capabilities of every device in the output of `getevent -p`.
+    pub fn all_from_getevent(
+        reader: &mut (impl BufRead + ?Sized),
+    ) -> Result<BTreeMap<String, Self>, Error> {
+        Self::parse_getevent(reader, None)
+    }
+
+    /// Reads the capabilities of `device`, or of every device if it is `None`.
+    fn parse_getevent(
+        reader: &mut (impl BufRead + ?Sized),
+        device: Option<&str>,
+    ) -> Result<BTreeMap<String, Self>, Error> {
+        let mut devices = BTreeMap::new();
+        let mut info: Option<&mut DeviceInfo> = None;
+        // Event type of the section of the `events:` list the current line belongs to.
+        let mut section = None;
+        // Whether the current line belongs to the `input props:` list.
+        let mut props = false;
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if let Some(rest) = line.strip_prefix("add device ") {
+                let (_, path) = rest.split_once(": ").ok_or_else(|| {
+                    Error::Line(
+                        index + 1,
+                        Box::new(Error::Format(format!("missing path in {:?}", line))),
+                    )
+                })?;
+                info = match device {
+                    Some(device) if device != path => None,
+                    _ => Some(devices.entry(path.to_string()).or_default()),
+                };
+                section = None;
+                props = false;
+                continue;
+            }
+            let info = match &mut info {
+                Some(info) => info,
+                None => continue,
+            };
+            if line == "input props:" {
+                props = true;
+                section = None;
+                continue;
+            }
+            let rest = if let Some((r#type, rest)) = section_header(line) {
+                section = Some(r#type);
+                props = false;
+                rest
+            } else if line.contains(':') && !line.contains(": value ") {
+                section = None;
+                props = false;
+                continue;
+            } else if props {
+                if line != "<none>" {
+                    let prop = gen::get_prop_value(line, Radix::Hex).map_err(|error| {
+                        Error::Line(
+                            index + 1,
+                            Box::new(Error::Parse(error, format!("parsing property {:?}", line))),
+                        )
+                    })?;
+                    info.props.insert(prop);
+                }
+                continue;
+            } else {
+                line
+            };
+            info.parse_section(section, rest)
+                .map_err(|error| Error::Line(index + 1, Box::new(error)))?;
+        }
+        Ok(devices)
+    }
+
+    fn parse_section(&mut self, r#type: Option<u16>, s: &str) -> Result<(), Error> {
+        let r#type = match r#type {
+            Some(r#type) => r#type,
+            None => return Ok(()),
+        };
+        let code = |s: &str| {
+            gen::get_code_value(r#type, s, Radix::Hex)
+                .map_err(|error| Error::Parse(error, format!("parsing code {:?}", s)))
+        };
+        match i32::from(r#type) {
+            input_event_codes::EV_KEY => {
+                for name in s.split_whitespace() {
+                    self.keys.insert(code(name)?);
+                }
+            }
+            input_event_codes::EV_ABS => {
+                let (name, fields) = s
+                    .split_once(':')
+                    .ok_or_else(|| Error::Format(format!("missing `:` in {:?}", s)))?;
+                self.abs
+                    .insert(code(name.trim())?, parse_absinfo(fields.trim())?);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub fn abs(&self, code: i32) -> Option<&input_absinfo> {
+        self.abs.get(&u16::try_from(code).ok()?)
+    }
+
+    pub fn has_key(&self, code: i32) -> bool {
+        u16::try_from(code).is_ok_and(|code| self.keys.contains(&code))
+    }
+}
+
+/// Splits a line like `ABS (0003): ABS_X : value 0, ...` into the event type and the rest.
+fn section_header(line: &str) -> Option<(u16, &str)> {
+    let (_, rest) = line.split_once(" (")?;
+    let (r#type, rest) = rest.split_once("):")?;
+    Some((u16::from_str_radix(r#type, 16).ok()?, rest.trim_start()))
+}
+
+/// Parses `value 0, min 0, max 1079, fuzz 0, flat 0, resolution 0`.
+fn parse_absinfo(s: &str) -> Result<input_absinfo, Error> {
+    let mut absinfo = input_absinfo::default();
+    for field in s.split(", ") {
+        let (key, value) = field
+            .split_once(' ')
+            .ok_or_else(|| Error::Format(format!("missing value in {:?}", field)))?;
+        let value = value
+            .parse()
+            .map_err(|error| Error::ParseInt(error, format!("parsing {:?}", field)))?;
+        match key {
+            "value" => absinfo.value = value,
+            "min" => absinfo.minimum = value,
+            "max" => absinfo.maximum = value,
+            "fuzz" => absinfo.fuzz = value,
+            "flat" => absinfo.flat = value,
+            "resolution" => absinfo.resolution = value,
+            _ => return Err(Error::Format(format!("unknown field {:?}", key))),
+        }
+    }
+    Ok(absinfo)
+}
+
+/// Codes of an event type that the device supports, according to `EVIOCGBIT`.
+fn bits(fd: RawFd, r#type: i32, max: i32) -> Result<Vec<u16>, Error> {
+    let mut buffer = vec![0u8; max as usize / 8 + 1];
+    let request = input::EVIOCGBIT(r#type as u32, buffer.len() as u32);
+    if unsafe { libc::ioctl(fd, request as _, buffer.as_mut_ptr()) } < 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    Ok(set_bits(&buffer, max))
+}
+
+/// Bits up to `max` that are set in a bitmask as returned by the `EVIOCG*` ioctls.
+fn set_bits<B: FromIterator<u16>>(buffer: &[u8], max: i32) -> B {
+    (0..=max as u16)
+        .filter(|&code| buffer[usize::from(code / 8)] & (1 << (code % 8)) != 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeviceInfo;
+    use crate::linux::{input::input_absinfo, input_event_codes};
+
+    const DUMP: &str = "\
+add device 1: /dev/input/event0
+  name:     \"gpio-keys\"
+  events:
+    KEY (0001): KEY_VOLUMEDOWN        KEY_VOLUMEUP          KEY_POWER
+  input props:
+    <none>
+add device 2: /dev/input/event2
+  name:     \"touchscreen\"
+  events:
+    KEY (0001): BTN_TOUCH
+    ABS (0003): ABS_MT_SLOT           : value 0, min 0, max 9, fuzz 0, flat 0, resolution 0
+                ABS_MT_POSITION_X     : value 0, min 0, max 1079, fuzz 0, flat 0, resolution 0
+                0036                  : value 0, min 0, max 2339, fuzz 0, flat 0, resolution 0
+  input props:
+    INPUT_PROP_DIRECT
+";
+
+    #[test]
+    fn from_getevent() {
+        let info = DeviceInfo::from_getevent(&mut DUMP.as_bytes(), "/dev/input/event2").unwrap();
+        assert!(info.has_key(input_event_codes::BTN_TOUCH));
+        assert!(!info.has_key(input_event_codes::KEY_POWER));
+        assert_eq!(info.abs.len(), 3);
+        assert_eq!(
+            info.abs(input_event_codes::ABS_MT_POSITION_Y),
+            Some(&input_absinfo {
+                maximum: 2339,
+                ..input_absinfo::default()
+            })
+        );
+
+        assert_eq!(
+            info.props,
+            [input_event_codes::INPUT_PROP_DIRECT as u16].into()
+        );
+
+        let info = DeviceInfo::from_getevent(&mut DUMP.as_bytes(), "/dev/input/event0").unwrap();
+        assert_eq!(info.keys.len(), 3);
+        assert!(info.abs.is_empty());
+        assert!(info.props.is_empty());
+
+        assert!(DeviceInfo::from_getevent(&mut DUMP.as_bytes(), "/dev/input/event1").is_err());
+        assert_eq!(
+            DeviceInfo::all_from_getevent(&mut DUMP.as_bytes())
+                .unwrap()
+                .keys()
+                .collect::<Vec<_>>(),
+            ["/dev/input/event0", "/dev/input/event2"]
+        );
+        let bad =
+            "add device 1: /dev/input/event2\n  events:\n    ABS (0003): ABS_X : value 0, min\n";
+        assert!(DeviceInfo::from_getevent(&mut bad.as_bytes(), "/dev/input/event2").is_err());
+        // Other devices may report codes that are not known here.
+        let other =
+            "add device 1: /dev/input/event1\n  events:\n    ABS (0003): ABS_FOO : value 0\n";
+        let dump = format!("{}{}", other, DUMP);
+        assert!(DeviceInfo::from_getevent(&mut dump.as_bytes(), "/dev/input/event2").is_ok());
+        assert!(DeviceInfo::all_from_getevent(&mut dump.as_bytes()).is_err());
+    }
+}
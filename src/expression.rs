@@ -0,0 +1,149 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Evaluates an arithmetic expression of numbers, variables, `+`, `-`, `*`, `/`, `%` and
+/// parentheses.
+pub(crate) fn evaluate(s: &str, variable: &dyn Fn(&str) -> Option<f64>) -> Result<f64, String> {
+    let mut parser = Parser {
+        chars: s.chars().peekable(),
+        variable,
+    };
+    let value = parser.sum()?;
+    match parser.peek() {
+        None => Ok(value),
+        Some(c) => Err(format!("unexpected `{}` in expression {:?}", c, s)),
+    }
+}
+
+/// Formats a number without a fractional part like an integer.
+pub(crate) fn format(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{}", value)
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    variable: &'a dyn Fn(&str) -> Option<f64>,
+}
+
+impl Parser<'_> {
+    /// The next character that is not whitespace.
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+        self.chars.peek().copied()
+    }
+
+    fn sum(&mut self) -> Result<f64, String> {
+        let mut value = self.product()?;
+        loop {
+            match self.peek() {
+                Some('+') => {
+                    self.chars.next();
+                    value += self.product()?;
+                }
+                Some('-') => {
+                    self.chars.next();
+                    value -= self.product()?;
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    fn product(&mut self) -> Result<f64, String> {
+        let mut value = self.factor()?;
+        loop {
+            let operator = match self.peek() {
+                Some(c @ ('*' | '/' | '%')) => c,
+                _ => return Ok(value),
+            };
+            self.chars.next();
+            let operand = self.factor()?;
+            if operator != '*' && operand == 0.0 {
+                return Err("division by zero".to_string());
+            }
+            match operator {
+                '*' => value *= operand,
+                '/' => value /= operand,
+                _ => value %= operand,
+            }
+        }
+    }
+
+    fn factor(&mut self) -> Result<f64, String> {
+        match self.peek() {
+            Some('-') => {
+                self.chars.next();
+                Ok(-self.factor()?)
+            }
+            Some('(') => {
+                self.chars.next();
+                let value = self.sum()?;
+                match self.peek() {
+                    Some(')') => {
+                        self.chars.next();
+                        Ok(value)
+                    }
+                    _ => Err("missing `)`".to_string()),
+                }
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let number = self.take(|c| c.is_ascii_digit() || c == '.');
+                number
+                    .parse()
+                    .map_err(|_| format!("invalid number {:?}", number))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let name = self.take(|c| c.is_alphanumeric() || c == '_');
+                (self.variable)(&name).ok_or_else(|| format!("unknown variable {:?}", name))
+            }
+            Some(c) => Err(format!("unexpected `{}`", c)),
+            None => Err("missing operand".to_string()),
+        }
+    }
+
+    fn take(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let mut s = String::new();
+        while let Some(c) = self.chars.next_if(|&c| predicate(c)) {
+            s.push(c);
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{evaluate, format};
+
+    fn variable(name: &str) -> Option<f64> {
+        match name {
+            "x" => Some(540.0),
+            "step_2" => Some(2.5),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(evaluate("1 + 2 * 3", &variable), Ok(7.0));
+        assert_eq!(evaluate("(1 + 2) * 3", &variable), Ok(9.0));
+        assert_eq!(evaluate("x / 2 - -10", &variable), Ok(280.0));
+        assert_eq!(evaluate("7 % 4 + step_2", &variable), Ok(5.5));
+        assert_eq!(evaluate("x-1", &variable), Ok(539.0));
+        assert!(evaluate("y", &variable).is_err());
+        assert!(evaluate("1 / 0", &variable).is_err());
+        assert!(evaluate("(1", &variable).is_err());
+        assert!(evaluate("1 2", &variable).is_err());
+        assert!(evaluate("", &variable).is_err());
+    }
+
+    #[test]
+    fn format_number() {
+        assert_eq!(format(540.0), "540");
+        assert_eq!(format(-3.0), "-3");
+        assert_eq!(format(2.5), "2.5");
+    }
+}
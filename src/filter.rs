@@ -0,0 +1,240 @@
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use crate::{gen, is_syn_report, InputEvent, Packet, Radix};
+
+/// Matches events by device, type, code and value. Fields that are `None` match anything.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Rule {
+    /// Glob pattern where `*` matches any sequence of characters and `?` any single character.
+    pub device: Option<String>,
+    pub r#type: Option<u16>,
+    pub code: Option<u16>,
+    pub value: Option<RangeInclusive<i32>>,
+}
+
+impl Rule {
+    fn matches(&self, device: &str, event: &InputEvent) -> bool {
+        self.device
+            .as_ref()
+            .is_none_or(|pattern| glob_match(pattern, device))
+            && self.r#type.is_none_or(|r#type| r#type == event.r#type)
+            && self.code.is_none_or(|code| code == event.code)
+            && self
+                .value
+                .as_ref()
+                .is_none_or(|value| value.contains(&event.value))
+    }
+}
+
+/// Parses comma separated `key=value` fields, for example
+/// `device=/dev/input/event*,type=EV_MSC,code=MSC_SCAN` or `type=EV_KEY,value=DOWN..REPEAT`.
+///
+/// Types, codes and values are names or numbers like in the input, which are hexadecimal unless
+/// a `0d` prefix makes them decimal. Codes and values are looked up in the tables of the given
+/// type, so `code` requires `type`. Value ranges are inclusive and either bound may be omitted.
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rule = Rule::default();
+        let mut code = None;
+        let mut value = None;
+        for field in s.split(',') {
+            let (key, argument) = field
+                .split_once('=')
+                .ok_or_else(|| format!("missing `=` in filter field {:?}", field))?;
+            match key {
+                "device" => rule.device = Some(argument.to_string()),
+                "type" => {
+                    rule.r#type = Some(
+                        gen::get_type_value(argument, Radix::Hex)
+                            .map_err(|error| format!("{} in filter type {:?}", error, argument))?,
+                    )
+                }
+                "code" => code = Some(argument),
+                "value" => value = Some(argument),
+                _ => return Err(format!("unknown filter field {:?}", key)),
+            }
+        }
+        if let Some(code) = code {
+            let r#type = rule
+                .r#type
+                .ok_or_else(|| format!("filter code {:?} requires a type", code))?;
+            rule.code = Some(
+                gen::get_code_value(r#type, code, Radix::Hex)
+                    .map_err(|error| format!("{} in filter code {:?}", error, code))?,
+            );
+        }
+        if let Some(value) = value {
+            let r#type = rule.r#type.unwrap_or(0);
+            let code = rule.code.unwrap_or(0);
+            let parse = |bound: &str, default| {
+                if bound.is_empty() {
+                    Ok(default)
+                } else {
+                    gen::get_value_value(r#type, code, bound, Radix::Hex)
+                        .map_err(|error| format!("{} in filter value {:?}", error, bound))
+                }
+            };
+            let (start, end) = match value.split_once("..") {
+                Some((start, end)) => (parse(start, i32::MIN)?, parse(end, i32::MAX)?),
+                None => {
+                    let value = parse(value, 0)?;
+                    (value, value)
+                }
+            };
+            rule.value = Some(start..=end);
+        }
+        Ok(rule)
+    }
+}
+
+/// Keeps events that match any of the include rules, or all events if there are none, and
+/// that match none of the exclude rules.
+#[derive(Debug, Default, Clone)]
+pub struct Filter {
+    pub include: Vec<Rule>,
+    pub exclude: Vec<Rule>,
+}
+
+impl Filter {
+    fn keeps(&self, device: &str, event: &InputEvent) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|rule| rule.matches(device, event)))
+            && !self.exclude.iter().any(|rule| rule.matches(device, event))
+    }
+
+    /// Filters the events of a packet. The terminating `SYN_REPORT` event is always kept, unless
+    /// all other events of the packet are removed, in which case the whole packet is dropped.
+    pub(crate) fn apply(&self, mut packet: Packet) -> Option<Packet> {
+        if self.include.is_empty() && self.exclude.is_empty() {
+            return Some(packet);
+        }
+        let len = packet.events.len();
+        let device = &packet.device;
+        packet
+            .events
+            .retain(|event| is_syn_report(event) || self.keeps(device, event));
+        if packet.events.len() < len && packet.events.iter().all(is_syn_report) {
+            None
+        } else {
+            Some(packet)
+        }
+    }
+}
+
+fn glob_match(pattern: &str, s: &str) -> bool {
+    fn matches(pattern: &[char], s: &[char]) -> bool {
+        match pattern.split_first() {
+            None => s.is_empty(),
+            Some(('*', rest)) => (0..=s.len()).any(|i| matches(rest, &s[i..])),
+            Some(('?', rest)) => !s.is_empty() && matches(rest, &s[1..]),
+            Some((c, rest)) => s.first() == Some(c) && matches(rest, &s[1..]),
+        }
+    }
+
+    matches(
+        &pattern.chars().collect::<Vec<_>>(),
+        &s.chars().collect::<Vec<_>>(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glob_match, Filter, Rule};
+    use crate::linux::input_event_codes;
+    use crate::{packets, parse_all};
+
+    #[test]
+    fn parse_rule() {
+        assert_eq!(
+            "device=/dev/input/event*,type=EV_MSC,code=MSC_SCAN"
+                .parse::<Rule>()
+                .unwrap(),
+            Rule {
+                device: Some("/dev/input/event*".to_string()),
+                r#type: Some(input_event_codes::EV_MSC.try_into().unwrap()),
+                code: Some(input_event_codes::MSC_SCAN.try_into().unwrap()),
+                value: None,
+            }
+        );
+        assert_eq!(
+            "type=EV_KEY,code=0074,value=DOWN.."
+                .parse::<Rule>()
+                .unwrap(),
+            Rule {
+                device: None,
+                r#type: Some(input_event_codes::EV_KEY.try_into().unwrap()),
+                code: Some(input_event_codes::KEY_POWER.try_into().unwrap()),
+                value: Some(1..=i32::MAX),
+            }
+        );
+        assert_eq!("value=10".parse::<Rule>().unwrap().value, Some(0x10..=0x10));
+        assert_eq!(
+            "type=0d3,code=0x35,value=-0d1..0d540"
+                .parse::<Rule>()
+                .unwrap(),
+            Rule {
+                device: None,
+                r#type: Some(input_event_codes::EV_ABS.try_into().unwrap()),
+                code: Some(input_event_codes::ABS_MT_POSITION_X.try_into().unwrap()),
+                value: Some(-1..=540),
+            }
+        );
+        assert!("code=MSC_SCAN".parse::<Rule>().is_err());
+        assert!("type=EV_MSC,code=SCAN".parse::<Rule>().is_err());
+        assert!("kind=EV_MSC".parse::<Rule>().is_err());
+        assert!("EV_MSC".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn glob() {
+        assert!(glob_match("/dev/input/event*", "/dev/input/event12"));
+        assert!(glob_match("/dev/input/event?", "/dev/input/event1"));
+        assert!(!glob_match("/dev/input/event?", "/dev/input/event12"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("/dev/input/event1", "/dev/input/event12"));
+    }
+
+    #[test]
+    fn apply() {
+        let input = "\
+[       1.000000] /dev/input/event0: EV_MSC MSC_SCAN 00070004
+[       1.000000] /dev/input/event0: EV_KEY KEY_A DOWN
+[       1.000000] /dev/input/event0: EV_SYN SYN_REPORT 00000000
+[       1.000000] /dev/input/event0: EV_MSC MSC_SCAN 00070004
+[       1.000000] /dev/input/event0: EV_SYN SYN_REPORT 00000000
+[       1.000000] /dev/input/event4: EV_ABS ABS_X 00000001
+[       1.000000] /dev/input/event4: EV_SYN SYN_REPORT 00000000
+[       1.000000] /dev/input/event0: EV_SYN SYN_REPORT 00000000
+";
+        let filter = Filter {
+            include: vec![],
+            exclude: vec![
+                "type=EV_MSC,code=MSC_SCAN".parse().unwrap(),
+                "device=*4".parse().unwrap(),
+            ],
+        };
+        let result = packets(parse_all(&mut input.as_bytes()), None)
+            .filter_map(|packet| filter.apply(packet.unwrap()))
+            .map(|packet| (packet.device, packet.events.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            result,
+            [
+                ("/dev/input/event0".to_string(), 2),
+                ("/dev/input/event0".to_string(), 1),
+            ]
+        );
+
+        let filter = Filter {
+            include: vec!["type=EV_KEY".parse().unwrap()],
+            exclude: vec![],
+        };
+        let result = packets(parse_all(&mut input.as_bytes()), None)
+            .filter_map(|packet| filter.apply(packet.unwrap()))
+            .map(|packet| packet.events.len())
+            .collect::<Vec<_>>();
+        assert_eq!(result, [2, 1]);
+    }
+}
@@ -0,0 +1,644 @@
+use std::collections::BTreeSet;
+use std::f64::consts::PI;
+use std::time::Duration;
+
+use crate::device::DeviceInfo;
+use crate::linux::input_event_codes;
+use crate::{Error, Packet};
+
+/// A position in device units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Where a contact is while it touches the device.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Path {
+    /// Stays at one point.
+    Stay(Point),
+    /// Moves along a straight line.
+    Line { from: Point, to: Point },
+    /// Moves along a quadratic Bézier curve that bends towards `control`.
+    Curve {
+        from: Point,
+        control: Point,
+        to: Point,
+    },
+    /// Moves around `center` while the radius and the angle, in degrees, change linearly.
+    Arc {
+        center: Point,
+        radius: (f64, f64),
+        angle: (f64, f64),
+    },
+}
+
+impl Path {
+    /// The position after the fraction `t` in `0.0..=1.0` of the movement.
+    fn at(&self, t: f64) -> Point {
+        let lerp = |a: f64, b: f64| a + (b - a) * t;
+        match *self {
+            Path::Stay(point) => point,
+            Path::Line { from, to } => Point {
+                x: lerp(from.x, to.x),
+                y: lerp(from.y, to.y),
+            },
+            Path::Curve { from, control, to } => {
+                let s = 1.0 - t;
+                Point {
+                    x: s * s * from.x + 2.0 * s * t * control.x + t * t * to.x,
+                    y: s * s * from.y + 2.0 * s * t * control.y + t * t * to.y,
+                }
+            }
+            Path::Arc {
+                center,
+                radius,
+                angle,
+            } => {
+                let radius = lerp(radius.0, radius.1);
+                let angle = lerp(angle.0, angle.1) * PI / 180.0;
+                Point {
+                    x: center.x + radius * angle.cos(),
+                    y: center.y + radius * angle.sin(),
+                }
+            }
+        }
+    }
+}
+
+/// A finger that touches the device at `start` and lifts `duration` later.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contact {
+    pub start: Duration,
+    pub duration: Duration,
+    pub path: Path,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Gesture {
+    pub contacts: Vec<Contact>,
+}
+
+impl Gesture {
+    pub fn tap(point: Point, duration: Duration) -> Self {
+        Self::hold(Duration::ZERO, duration, Path::Stay(point))
+    }
+
+    /// Two taps of `duration` each, the second starting `interval` after the first ends.
+    pub fn double_tap(point: Point, duration: Duration, interval: Duration) -> Self {
+        let mut gesture = Self::tap(point, duration);
+        gesture.contacts.push(Contact {
+            start: duration + interval,
+            duration,
+            path: Path::Stay(point),
+        });
+        gesture
+    }
+
+    pub fn long_press(point: Point, duration: Duration) -> Self {
+        Self::tap(point, duration)
+    }
+
+    pub fn swipe(from: Point, to: Point, duration: Duration) -> Self {
+        Self::hold(Duration::ZERO, duration, Path::Line { from, to })
+    }
+
+    pub fn curved_swipe(from: Point, control: Point, to: Point, duration: Duration) -> Self {
+        Self::hold(Duration::ZERO, duration, Path::Curve { from, control, to })
+    }
+
+    /// Two fingers on opposite sides of `center` at `angle` degrees, moving from `from` to `to`
+    /// apart.
+    pub fn pinch(center: Point, from: f64, to: f64, angle: f64, duration: Duration) -> Self {
+        Self::two_fingers(center, (from / 2.0, to / 2.0), (angle, angle), duration)
+    }
+
+    /// Two fingers on opposite sides of `center`, `radius` away from it, turning from `from` to
+    /// `to` degrees.
+    pub fn rotate(center: Point, radius: f64, from: f64, to: f64, duration: Duration) -> Self {
+        Self::two_fingers(center, (radius, radius), (from, to), duration)
+    }
+
+    fn hold(start: Duration, duration: Duration, path: Path) -> Self {
+        Gesture {
+            contacts: vec![Contact {
+                start,
+                duration,
+                path,
+            }],
+        }
+    }
+
+    fn two_fingers(
+        center: Point,
+        radius: (f64, f64),
+        angle: (f64, f64),
+        duration: Duration,
+    ) -> Self {
+        let contact = |offset| Contact {
+            start: Duration::ZERO,
+            duration,
+            path: Path::Arc {
+                center,
+                radius,
+                angle: (angle.0 + offset, angle.1 + offset),
+            },
+        };
+        Gesture {
+            contacts: vec![contact(0.0), contact(180.0)],
+        }
+    }
+}
+
+/// State of a contact while generating a gesture.
+#[derive(Debug, Default)]
+struct ContactState {
+    slot: Option<i32>,
+    released: bool,
+    point: Option<Point>,
+    /// Last reported `ABS_MT_POSITION_X` and `ABS_MT_POSITION_Y`.
+    position: Option<(i32, i32)>,
+}
+
+/// Turns gestures into packets for one device, of multitouch protocol B if it reports
+/// `ABS_MT_SLOT` and of protocol A otherwise.
+#[derive(Debug)]
+pub struct GestureGenerator {
+    device: String,
+    info: DeviceInfo,
+    /// Samples per second while contacts move.
+    pub rate: u32,
+    next_tracking_id: i32,
+}
+
+impl GestureGenerator {
+    pub fn new(device: &str, info: DeviceInfo) -> Self {
+        let next_tracking_id = info
+            .abs(input_event_codes::ABS_MT_TRACKING_ID)
+            .map_or(0, |absinfo| absinfo.minimum.max(0));
+        GestureGenerator {
+            device: device.to_string(),
+            info,
+            rate: 60,
+            next_tracking_id,
+        }
+    }
+
+    fn has(&self, code: i32) -> bool {
+        self.info.abs(code).is_some()
+    }
+
+    /// Rounds and clamps a coordinate to the range of `code`.
+    fn value(&self, code: i32, value: f64) -> i32 {
+        let value = value.round() as i32;
+        match self.info.abs(code) {
+            Some(absinfo) if absinfo.minimum <= absinfo.maximum => {
+                value.clamp(absinfo.minimum, absinfo.maximum)
+            }
+            _ => value,
+        }
+    }
+
+    /// Allocates tracking IDs in increasing order, wrapping around at the maximum.
+    fn tracking_id(&mut self) -> i32 {
+        let id = self.next_tracking_id;
+        self.next_tracking_id = match self.info.abs(input_event_codes::ABS_MT_TRACKING_ID) {
+            Some(absinfo) if id >= absinfo.maximum => absinfo.minimum.max(0),
+            _ => id.checked_add(1).unwrap_or(0),
+        };
+        id
+    }
+
+    /// Generates the packets of a gesture, timestamped relative to its start. Positions are
+    /// sampled at [`GestureGenerator::rate`] and whenever a contact touches or lifts.
+    pub(crate) fn generate(&mut self, gesture: &Gesture) -> Result<Vec<Packet>, Error> {
+        let multitouch = self.has(input_event_codes::ABS_MT_POSITION_X)
+            && self.has(input_event_codes::ABS_MT_POSITION_Y);
+        let single_touch = self.has(input_event_codes::ABS_X) && self.has(input_event_codes::ABS_Y);
+        if !multitouch && !single_touch {
+            return Err(Error::Format(format!(
+                "{} reports neither ABS_MT_POSITION_X/Y nor ABS_X/Y",
+                self.device
+            )));
+        }
+        let has_slots = multitouch && self.has(input_event_codes::ABS_MT_SLOT);
+        // Contacts of protocol B only lift when their tracking ID is set to -1.
+        if has_slots && !self.has(input_event_codes::ABS_MT_TRACKING_ID) {
+            return Err(Error::Format(format!(
+                "{} reports ABS_MT_SLOT but not ABS_MT_TRACKING_ID",
+                self.device
+            )));
+        }
+        // Contacts of protocol A are anonymous, so any number of them can touch.
+        let slots = match self.info.abs(input_event_codes::ABS_MT_SLOT) {
+            Some(absinfo) if has_slots => absinfo.maximum + 1,
+            _ if multitouch => gesture.contacts.len().try_into().unwrap_or(i32::MAX),
+            _ => 1,
+        };
+        let pressure = self
+            .info
+            .abs(input_event_codes::ABS_MT_PRESSURE)
+            .map(|absinfo| absinfo.minimum + (absinfo.maximum - absinfo.minimum) / 2);
+        let has_btn_touch = self.info.has_key(input_event_codes::BTN_TOUCH);
+
+        let micros = |duration: Duration| duration.as_micros() as u64;
+        let end = gesture
+            .contacts
+            .iter()
+            .map(|contact| micros(contact.start + contact.duration))
+            .max()
+            .unwrap_or(0);
+        let period = (1_000_000 / u64::from(self.rate.max(1))).max(1);
+        let mut times: BTreeSet<u64> = (0..=end).step_by(period as usize).collect();
+        for contact in &gesture.contacts {
+            times.insert(micros(contact.start));
+            times.insert(micros(contact.start + contact.duration));
+        }
+
+        let mut states: Vec<ContactState> = gesture
+            .contacts
+            .iter()
+            .map(|_| ContactState::default())
+            .collect();
+        let mut current_slot = None;
+        let mut touching = false;
+        let mut single_touch_position = None;
+        // Whether the last packet of protocol A reported any contacts.
+        let mut reported = false;
+        let mut result = vec![];
+        for time in times {
+            // Event type, code and value of the events of this packet.
+            let mut events: Vec<(i32, i32, i32)> = vec![];
+            for (index, contact) in gesture.contacts.iter().enumerate() {
+                let (start, duration) = (micros(contact.start), micros(contact.duration));
+                if time < start || states[index].released {
+                    continue;
+                }
+                let down = states[index].slot.is_none();
+                if down {
+                    let slot = (0..slots)
+                        .find(|&slot| {
+                            !states
+                                .iter()
+                                .any(|state| !state.released && state.slot == Some(slot))
+                        })
+                        .ok_or_else(|| {
+                            Error::Format(format!(
+                                "{} supports at most {} simultaneous contacts",
+                                self.device, slots
+                            ))
+                        })?;
+                    states[index].slot = Some(slot);
+                }
+                let state = &mut states[index];
+                let point = contact.path.at(if duration == 0 {
+                    1.0
+                } else {
+                    ((time - start) as f64 / duration as f64).min(1.0)
+                });
+                state.point = Some(point);
+                let released = time >= start + duration;
+                if multitouch && !has_slots {
+                    // Protocol A reports every contact that touches in every packet.
+                    if !released {
+                        events.push((
+                            input_event_codes::EV_ABS,
+                            input_event_codes::ABS_MT_POSITION_X,
+                            self.value(input_event_codes::ABS_MT_POSITION_X, point.x),
+                        ));
+                        events.push((
+                            input_event_codes::EV_ABS,
+                            input_event_codes::ABS_MT_POSITION_Y,
+                            self.value(input_event_codes::ABS_MT_POSITION_Y, point.y),
+                        ));
+                        if let Some(pressure) = pressure {
+                            events.push((
+                                input_event_codes::EV_ABS,
+                                input_event_codes::ABS_MT_PRESSURE,
+                                pressure,
+                            ));
+                        }
+                        events.push((
+                            input_event_codes::EV_SYN,
+                            input_event_codes::SYN_MT_REPORT,
+                            0,
+                        ));
+                    }
+                } else if multitouch {
+                    let slot = state.slot.unwrap();
+                    if has_slots && current_slot != Some(slot) {
+                        events.push((
+                            input_event_codes::EV_ABS,
+                            input_event_codes::ABS_MT_SLOT,
+                            slot,
+                        ));
+                        current_slot = Some(slot);
+                    }
+                    if down {
+                        events.push((
+                            input_event_codes::EV_ABS,
+                            input_event_codes::ABS_MT_TRACKING_ID,
+                            self.tracking_id(),
+                        ));
+                    }
+                    let x = self.value(input_event_codes::ABS_MT_POSITION_X, point.x);
+                    let y = self.value(input_event_codes::ABS_MT_POSITION_Y, point.y);
+                    if state.position.map(|(x, _)| x) != Some(x) {
+                        events.push((
+                            input_event_codes::EV_ABS,
+                            input_event_codes::ABS_MT_POSITION_X,
+                            x,
+                        ));
+                    }
+                    if state.position.map(|(_, y)| y) != Some(y) {
+                        events.push((
+                            input_event_codes::EV_ABS,
+                            input_event_codes::ABS_MT_POSITION_Y,
+                            y,
+                        ));
+                    }
+                    state.position = Some((x, y));
+                    if let (true, Some(pressure)) = (down, pressure) {
+                        events.push((
+                            input_event_codes::EV_ABS,
+                            input_event_codes::ABS_MT_PRESSURE,
+                            pressure,
+                        ));
+                    }
+                    if released {
+                        events.push((
+                            input_event_codes::EV_ABS,
+                            input_event_codes::ABS_MT_TRACKING_ID,
+                            -1,
+                        ));
+                    }
+                }
+                state.released = released;
+            }
+            if multitouch && !has_slots {
+                let reporting = !events.is_empty();
+                // A single `SYN_MT_REPORT` reports that the last contact was lifted.
+                if !reporting && reported {
+                    events.push((
+                        input_event_codes::EV_SYN,
+                        input_event_codes::SYN_MT_REPORT,
+                        0,
+                    ));
+                }
+                reported = reporting;
+            }
+
+            // Like the kernel's pointer emulation, the oldest contact that still touches drives
+            // `BTN_TOUCH` and the single touch axes.
+            let primary = states
+                .iter()
+                .find(|state| state.slot.is_some() && !state.released);
+            if has_btn_touch && primary.is_some() != touching {
+                touching = primary.is_some();
+                events.push((
+                    input_event_codes::EV_KEY,
+                    input_event_codes::BTN_TOUCH,
+                    touching.into(),
+                ));
+            }
+            if let (true, Some(point)) = (single_touch, primary.and_then(|state| state.point)) {
+                let x = self.value(input_event_codes::ABS_X, point.x);
+                let y = self.value(input_event_codes::ABS_Y, point.y);
+                if single_touch_position.map(|(x, _)| x) != Some(x) {
+                    events.push((input_event_codes::EV_ABS, input_event_codes::ABS_X, x));
+                }
+                if single_touch_position.map(|(_, y)| y) != Some(y) {
+                    events.push((input_event_codes::EV_ABS, input_event_codes::ABS_Y, y));
+                }
+                single_touch_position = Some((x, y));
+            }
+
+            if events.is_empty() {
+                continue;
+            }
+            result.push(Packet::new(
+                &self.device,
+                Duration::from_micros(time),
+                events,
+            ));
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gesture, GestureGenerator, Point};
+    use crate::device::DeviceInfo;
+    use std::time::Duration;
+
+    const TOUCHSCREEN: &str = "\
+add device 2: /dev/input/event2
+  events:
+    KEY (0001): BTN_TOUCH
+    ABS (0003): ABS_MT_SLOT           : value 0, min 0, max 9, fuzz 0, flat 0, resolution 0
+                ABS_MT_POSITION_X     : value 0, min 0, max 1079, fuzz 0, flat 0, resolution 0
+                ABS_MT_POSITION_Y     : value 0, min 0, max 2339, fuzz 0, flat 0, resolution 0
+                ABS_MT_TRACKING_ID    : value 0, min 0, max 2, fuzz 0, flat 0, resolution 0
+                ABS_MT_PRESSURE       : value 0, min 0, max 255, fuzz 0, flat 0, resolution 0
+";
+    const PROTOCOL_A: &str = "\
+add device 4: /dev/input/event4
+  events:
+    ABS (0003): ABS_MT_POSITION_X     : value 0, min 0, max 1079, fuzz 0, flat 0, resolution 0
+                ABS_MT_POSITION_Y     : value 0, min 0, max 2339, fuzz 0, flat 0, resolution 0
+";
+    const NO_TRACKING_ID: &str = "\
+add device 5: /dev/input/event5
+  events:
+    ABS (0003): ABS_MT_SLOT           : value 0, min 0, max 9, fuzz 0, flat 0, resolution 0
+                ABS_MT_POSITION_X     : value 0, min 0, max 1079, fuzz 0, flat 0, resolution 0
+                ABS_MT_POSITION_Y     : value 0, min 0, max 2339, fuzz 0, flat 0, resolution 0
+";
+    const SINGLE_TOUCH: &str = "\
+add device 3: /dev/input/event3
+  events:
+    KEY (0001): BTN_TOUCH
+    ABS (0003): ABS_X                 : value 0, min 0, max 1023, fuzz 0, flat 0, resolution 0
+                ABS_Y                 : value 0, min 0, max 767, fuzz 0, flat 0, resolution 0
+";
+
+    fn generator(dump: &str, device: &str) -> GestureGenerator {
+        GestureGenerator::new(
+            device,
+            DeviceInfo::from_getevent(&mut dump.as_bytes(), device).unwrap(),
+        )
+    }
+
+    /// Packets as lines of the offset in milliseconds and the events.
+    fn generate(generator: &mut GestureGenerator, gesture: &Gesture) -> Vec<String> {
+        generator
+            .generate(gesture)
+            .unwrap()
+            .iter()
+            .map(|packet| {
+                let events: Vec<_> = packet
+                    .events
+                    .iter()
+                    .map(|event| {
+                        event.to_string().split_whitespace().collect::<Vec<_>>()[1..].join(" ")
+                    })
+                    .collect();
+                format!("{} {}", packet.time().as_millis(), events.join(", "))
+            })
+            .collect()
+    }
+
+    const POINT: Point = Point { x: 100.0, y: 200.0 };
+
+    #[test]
+    fn tap() {
+        let mut generator = generator(TOUCHSCREEN, "/dev/input/event2");
+        assert_eq!(
+            generate(
+                &mut generator,
+                &Gesture::tap(POINT, Duration::from_millis(100))
+            ),
+            [
+                "0 ABS_MT_SLOT 00000000, ABS_MT_TRACKING_ID 00000000, \
+                 ABS_MT_POSITION_X 00000064, ABS_MT_POSITION_Y 000000c8, \
+                 ABS_MT_PRESSURE 0000007f, BTN_TOUCH DOWN, SYN_REPORT 00000000",
+                "100 ABS_MT_TRACKING_ID NONE, BTN_TOUCH UP, SYN_REPORT 00000000",
+            ]
+        );
+
+        // Tracking IDs keep increasing across gestures and wrap around at the maximum.
+        let gesture =
+            Gesture::double_tap(POINT, Duration::from_millis(20), Duration::from_millis(30));
+        let packets = generate(&mut generator, &gesture);
+        assert_eq!(packets.len(), 4);
+        assert!(packets[0].starts_with("0 ABS_MT_SLOT 00000000, ABS_MT_TRACKING_ID 00000001,"));
+        assert!(packets[2].starts_with("50 ABS_MT_TRACKING_ID 00000002,"));
+        assert_eq!(generator.tracking_id(), 0);
+    }
+
+    #[test]
+    fn swipe() {
+        let mut generator = generator(TOUCHSCREEN, "/dev/input/event2");
+        generator.rate = 10;
+        let gesture = Gesture::swipe(
+            POINT,
+            Point {
+                x: 2000.0,
+                y: 500.0,
+            },
+            Duration::from_millis(250),
+        );
+        let packets = generate(&mut generator, &gesture);
+        assert_eq!(packets.len(), 4);
+        assert_eq!(
+            packets[1],
+            "100 ABS_MT_POSITION_X 0000035c, ABS_MT_POSITION_Y 00000140, SYN_REPORT 00000000"
+        );
+        // Positions are clamped to the range of the axis.
+        assert_eq!(
+            packets[2],
+            "200 ABS_MT_POSITION_X 00000437, ABS_MT_POSITION_Y 000001b8, SYN_REPORT 00000000"
+        );
+        assert_eq!(
+            packets[3],
+            "250 ABS_MT_POSITION_Y 000001f4, ABS_MT_TRACKING_ID NONE, BTN_TOUCH UP, \
+             SYN_REPORT 00000000"
+        );
+
+        let gesture = Gesture::curved_swipe(
+            POINT,
+            Point { x: 500.0, y: 200.0 },
+            Point { x: 500.0, y: 600.0 },
+            Duration::from_millis(200),
+        );
+        assert_eq!(
+            generate(&mut generator, &gesture)[1],
+            "100 ABS_MT_POSITION_X 00000190, ABS_MT_POSITION_Y 0000012c, SYN_REPORT 00000000"
+        );
+    }
+
+    #[test]
+    fn pinch() {
+        let mut generator = generator(TOUCHSCREEN, "/dev/input/event2");
+        generator.rate = 10;
+        let gesture = Gesture::pinch(POINT, 100.0, 20.0, 0.0, Duration::from_millis(100));
+        assert_eq!(
+            generate(&mut generator, &gesture),
+            [
+                "0 ABS_MT_SLOT 00000000, ABS_MT_TRACKING_ID 00000000, \
+                 ABS_MT_POSITION_X 00000096, ABS_MT_POSITION_Y 000000c8, \
+                 ABS_MT_PRESSURE 0000007f, ABS_MT_SLOT 00000001, ABS_MT_TRACKING_ID 00000001, \
+                 ABS_MT_POSITION_X 00000032, ABS_MT_POSITION_Y 000000c8, \
+                 ABS_MT_PRESSURE 0000007f, BTN_TOUCH DOWN, SYN_REPORT 00000000",
+                "100 ABS_MT_SLOT 00000000, ABS_MT_POSITION_X 0000006e, \
+                 ABS_MT_TRACKING_ID NONE, ABS_MT_SLOT 00000001, \
+                 ABS_MT_POSITION_X 0000005a, ABS_MT_TRACKING_ID NONE, \
+                 BTN_TOUCH UP, SYN_REPORT 00000000",
+            ]
+        );
+
+        let gesture = Gesture::rotate(POINT, 50.0, 0.0, 90.0, Duration::from_millis(100));
+        let packets = generate(&mut generator, &gesture);
+        assert!(packets[1].starts_with(
+            "100 ABS_MT_SLOT 00000000, ABS_MT_POSITION_X 00000064, ABS_MT_POSITION_Y 000000fa"
+        ));
+    }
+
+    #[test]
+    fn protocol_a() {
+        let mut generator = generator(PROTOCOL_A, "/dev/input/event4");
+        generator.rate = 10;
+        let gesture = Gesture::pinch(POINT, 100.0, 20.0, 0.0, Duration::from_millis(100));
+        assert_eq!(
+            generate(&mut generator, &gesture),
+            [
+                "0 ABS_MT_POSITION_X 00000096, ABS_MT_POSITION_Y 000000c8, SYN_MT_REPORT 00000000, \
+                 ABS_MT_POSITION_X 00000032, ABS_MT_POSITION_Y 000000c8, SYN_MT_REPORT 00000000, \
+                 SYN_REPORT 00000000",
+                "100 SYN_MT_REPORT 00000000, SYN_REPORT 00000000",
+            ]
+        );
+    }
+
+    #[test]
+    fn no_tracking_id() {
+        let mut generator = generator(NO_TRACKING_ID, "/dev/input/event5");
+        let error = generator
+            .generate(&Gesture::tap(POINT, Duration::from_millis(100)))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "/dev/input/event5 reports ABS_MT_SLOT but not ABS_MT_TRACKING_ID"
+        );
+    }
+
+    #[test]
+    fn high_rate() {
+        let mut generator = generator(TOUCHSCREEN, "/dev/input/event2");
+        generator.rate = u32::MAX;
+        let packets = generate(
+            &mut generator,
+            &Gesture::tap(POINT, Duration::from_micros(2)),
+        );
+        assert_eq!(packets.len(), 2);
+    }
+
+    #[test]
+    fn single_touch() {
+        let mut generator = generator(SINGLE_TOUCH, "/dev/input/event3");
+        assert_eq!(
+            generate(
+                &mut generator,
+                &Gesture::tap(POINT, Duration::from_millis(100))
+            ),
+            [
+                "0 BTN_TOUCH DOWN, ABS_X 00000064, ABS_Y 000000c8, SYN_REPORT 00000000",
+                "100 BTN_TOUCH UP, SYN_REPORT 00000000",
+            ]
+        );
+        let gesture = Gesture::pinch(POINT, 100.0, 20.0, 0.0, Duration::from_millis(100));
+        assert!(generator.generate(&gesture).is_err());
+    }
+}
@@ -0,0 +1,371 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::linux::input_event_codes::*;
+use crate::mt::MtState;
+use crate::{gen, Packet};
+
+/// Statistics of the packets of one device.
+#[derive(Debug, Default)]
+pub struct DeviceStats {
+    pub packets: usize,
+    pub events: usize,
+    /// Number of events per type and code.
+    pub codes: BTreeMap<(u16, u16), usize>,
+    /// Most contacts that touched at the same time.
+    pub max_contacts: usize,
+    /// Smallest and largest x and y coordinate of the contacts.
+    pub x: Option<(i32, i32)>,
+    pub y: Option<(i32, i32)>,
+    /// Time of the first and the last packet.
+    pub first: Duration,
+    pub last: Duration,
+    mt: MtState,
+}
+
+impl DeviceStats {
+    /// Packets per second between the first and the last packet.
+    pub fn packet_rate(&self) -> f64 {
+        let duration = (self.last - self.first).as_secs_f64();
+        if duration > 0.0 {
+            (self.packets - 1) as f64 / duration
+        } else {
+            0.0
+        }
+    }
+
+    /// Number of events per type.
+    pub fn types(&self) -> BTreeMap<u16, usize> {
+        let mut types = BTreeMap::new();
+        for (&(r#type, _), count) in &self.codes {
+            *types.entry(r#type).or_default() += count;
+        }
+        types
+    }
+
+    fn record(&mut self, packet: &Packet, time: Duration) {
+        if self.packets == 0 {
+            self.first = time;
+        }
+        self.last = time;
+        self.packets += 1;
+        self.events += packet.events.len();
+        self.mt.packet(packet);
+        // Contacts reported with `SYN_MT_REPORT` in multitouch protocol A.
+        let mut reports = 0;
+        for event in &packet.events {
+            *self.codes.entry((event.r#type, event.code)).or_default() += 1;
+            let value = event.value;
+            match (i32::from(event.r#type), i32::from(event.code)) {
+                (EV_SYN, SYN_MT_REPORT) => reports += 1,
+                (EV_ABS, ABS_X | ABS_MT_POSITION_X) => extend(&mut self.x, value),
+                (EV_ABS, ABS_Y | ABS_MT_POSITION_Y) => extend(&mut self.y, value),
+                _ => {}
+            }
+        }
+        let contacts = self.mt.contacts().len().max(reports);
+        self.max_contacts = self.max_contacts.max(contacts);
+    }
+}
+
+fn extend(range: &mut Option<(i32, i32)>, value: i32) {
+    let (min, max) = range.get_or_insert((value, value));
+    *min = (*min).min(value);
+    *max = (*max).max(value);
+}
+
+/// Statistics of a recording.
+#[derive(Debug)]
+pub struct RecordingInfo {
+    pub devices: BTreeMap<String, DeviceStats>,
+    /// Gaps between consecutive packets longer than `gap_threshold`, as the time of the packet
+    /// before the gap and the length of the gap.
+    pub gaps: Vec<(Duration, Duration)>,
+    pub gap_threshold: Duration,
+    start: Option<Duration>,
+    previous: Duration,
+}
+
+impl RecordingInfo {
+    pub fn new(gap_threshold: Duration) -> Self {
+        RecordingInfo {
+            devices: BTreeMap::new(),
+            gaps: vec![],
+            gap_threshold,
+            start: None,
+            previous: Duration::ZERO,
+        }
+    }
+
+    pub(crate) fn record(&mut self, packet: &Packet) {
+        let start = *self.start.get_or_insert(packet.time());
+        let time = packet.time().saturating_sub(start);
+        if let Some(gap) = time.checked_sub(self.previous) {
+            if gap > self.gap_threshold {
+                self.gaps.push((self.previous, gap));
+            }
+        }
+        self.previous = time;
+        self.devices
+            .entry(packet.device.clone())
+            .or_default()
+            .record(packet, time);
+    }
+
+    /// Time from the first to the last packet.
+    pub fn duration(&self) -> Duration {
+        self.devices
+            .values()
+            .map(|device| device.last)
+            .max()
+            .unwrap_or_default()
+    }
+
+    pub fn packets(&self) -> usize {
+        self.devices.values().map(|device| device.packets).sum()
+    }
+
+    pub fn events(&self) -> usize {
+        self.devices.values().map(|device| device.events).sum()
+    }
+
+    pub fn write_text(&self, output: &mut impl Write) -> io::Result<()> {
+        writeln!(output, "duration: {}", seconds(self.duration()))?;
+        writeln!(
+            output,
+            "packets: {}, events: {}",
+            self.packets(),
+            self.events()
+        )?;
+        for (device, stats) in &self.devices {
+            writeln!(output, "{}:", device)?;
+            writeln!(
+                output,
+                "  packets: {}, events: {}, {:.1} packets per second",
+                stats.packets,
+                stats.events,
+                stats.packet_rate()
+            )?;
+            if stats.max_contacts > 0 {
+                writeln!(output, "  max contacts: {}", stats.max_contacts)?;
+            }
+            if let (Some(x), Some(y)) = (stats.x, stats.y) {
+                writeln!(output, "  bounds: x {}..={}, y {}..={}", x.0, x.1, y.0, y.1)?;
+            }
+            for (r#type, count) in stats.types() {
+                writeln!(output, "  {}: {}", type_name(r#type), count)?;
+                for (&(_, code), count) in stats.codes.range((r#type, 0)..=(r#type, u16::MAX)) {
+                    writeln!(output, "    {}: {}", code_name(r#type, code), count)?;
+                }
+            }
+        }
+        writeln!(
+            output,
+            "gaps longer than {}: {}",
+            seconds(self.gap_threshold),
+            self.gaps.len()
+        )?;
+        for (time, gap) in &self.gaps {
+            writeln!(output, "  at {}: {}", seconds(*time), seconds(*gap))?;
+        }
+        Ok(())
+    }
+
+    pub fn write_json(&self, output: &mut impl Write) -> io::Result<()> {
+        write!(
+            output,
+            "{{\"duration_us\":{},\"packets\":{},\"events\":{},\"devices\":{{",
+            self.duration().as_micros(),
+            self.packets(),
+            self.events()
+        )?;
+        for (index, (device, stats)) in self.devices.iter().enumerate() {
+            let range = |range: Option<(i32, i32)>| match range {
+                Some((min, max)) => format!("[{},{}]", min, max),
+                None => "null".to_string(),
+            };
+            write!(
+                output,
+                "{}{}:{{\"packets\":{},\"events\":{},\"packet_rate\":{:.3},\"max_contacts\":{},\
+                 \"x\":{},\"y\":{},\"types\":{{",
+                if index > 0 { "," } else { "" },
+                json_string(device),
+                stats.packets,
+                stats.events,
+                stats.packet_rate(),
+                stats.max_contacts,
+                range(stats.x),
+                range(stats.y)
+            )?;
+            for (index, (r#type, count)) in stats.types().into_iter().enumerate() {
+                let codes: Vec<_> = stats
+                    .codes
+                    .range((r#type, 0)..=(r#type, u16::MAX))
+                    .map(|(&(_, code), count)| {
+                        format!("{}:{}", json_string(&code_name(r#type, code)), count)
+                    })
+                    .collect();
+                write!(
+                    output,
+                    "{}{}:{{\"count\":{},\"codes\":{{{}}}}}",
+                    if index > 0 { "," } else { "" },
+                    json_string(&type_name(r#type)),
+                    count,
+                    codes.join(",")
+                )?;
+            }
+            write!(output, "}}}}")?;
+        }
+        let gaps: Vec<_> = self
+            .gaps
+            .iter()
+            .map(|(time, gap)| {
+                format!(
+                    "{{\"at_us\":{},\"length_us\":{}}}",
+                    time.as_micros(),
+                    gap.as_micros()
+                )
+            })
+            .collect();
+        writeln!(
+            output,
+            "}},\"gap_threshold_us\":{},\"gaps\":[{}]}}",
+            self.gap_threshold.as_micros(),
+            gaps.join(",")
+        )
+    }
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{}.{:06}s", duration.as_secs(), duration.subsec_micros())
+}
+
+fn type_name(r#type: u16) -> String {
+    gen::get_type_name(r#type)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{:04x}", r#type))
+}
+
+fn code_name(r#type: u16, code: u16) -> String {
+    gen::get_code_name(r#type, code)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{:04x}", code))
+}
+
+fn json_string(s: &str) -> String {
+    let mut result = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecordingInfo;
+    use crate::linux::input_event_codes::*;
+    use crate::Packet;
+    use std::time::Duration;
+
+    fn info() -> RecordingInfo {
+        let packet = |device, millis, events: &[(i32, i32, i32)]| {
+            Packet::new(device, Duration::from_millis(millis), events.to_vec())
+        };
+        let finger = |slot, id, x, y| {
+            [
+                (EV_ABS, ABS_MT_SLOT, slot),
+                (EV_ABS, ABS_MT_TRACKING_ID, id),
+                (EV_ABS, ABS_MT_POSITION_X, x),
+                (EV_ABS, ABS_MT_POSITION_Y, y),
+            ]
+        };
+        let touch = "/dev/input/event2";
+        let mut info = RecordingInfo::new(Duration::from_secs(1));
+        for packet in [
+            packet(touch, 5000, &finger(0, 1, 100, 200)),
+            packet(touch, 5010, &finger(1, 2, 300, 50)),
+            packet(touch, 5020, &[(EV_ABS, ABS_MT_TRACKING_ID, -1)]),
+            packet("/dev/input/event0", 7020, &[(EV_KEY, KEY_POWER, 1)]),
+        ] {
+            info.record(&packet);
+        }
+        info
+    }
+
+    #[test]
+    fn statistics() {
+        let info = info();
+        assert_eq!(info.duration(), Duration::from_millis(2020));
+        assert_eq!(info.packets(), 4);
+        assert_eq!(info.events(), 14);
+        assert_eq!(
+            info.gaps,
+            [(Duration::from_millis(20), Duration::from_secs(2))]
+        );
+        let touch = &info.devices["/dev/input/event2"];
+        assert_eq!(touch.max_contacts, 2);
+        assert_eq!(touch.x, Some((100, 300)));
+        assert_eq!(touch.y, Some((50, 200)));
+        assert_eq!(touch.packet_rate(), 100.0);
+        assert_eq!(touch.types()[&(EV_ABS as u16)], 9);
+    }
+
+    #[test]
+    fn write_text() {
+        let mut output = vec![];
+        info().write_text(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\
+duration: 2.020000s
+packets: 4, events: 14
+/dev/input/event0:
+  packets: 1, events: 2, 0.0 packets per second
+  EV_SYN: 1
+    SYN_REPORT: 1
+  EV_KEY: 1
+    KEY_POWER: 1
+/dev/input/event2:
+  packets: 3, events: 12, 100.0 packets per second
+  max contacts: 2
+  bounds: x 100..=300, y 50..=200
+  EV_SYN: 3
+    SYN_REPORT: 3
+  EV_ABS: 9
+    ABS_MT_SLOT: 2
+    ABS_MT_POSITION_X: 2
+    ABS_MT_POSITION_Y: 2
+    ABS_MT_TRACKING_ID: 3
+gaps longer than 1.000000s: 1
+  at 0.020000s: 2.000000s
+"
+        );
+    }
+
+    #[test]
+    fn write_json() {
+        let mut output = vec![];
+        info().write_json(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"duration_us\":2020000,\"packets\":4,\"events\":14,\"devices\":{\
+             \"/dev/input/event0\":{\"packets\":1,\"events\":2,\"packet_rate\":0.000,\
+             \"max_contacts\":0,\"x\":null,\"y\":null,\"types\":{\
+             \"EV_SYN\":{\"count\":1,\"codes\":{\"SYN_REPORT\":1}},\
+             \"EV_KEY\":{\"count\":1,\"codes\":{\"KEY_POWER\":1}}}},\
+             \"/dev/input/event2\":{\"packets\":3,\"events\":12,\"packet_rate\":100.000,\
+             \"max_contacts\":2,\"x\":[100,300],\"y\":[50,200],\"types\":{\
+             \"EV_SYN\":{\"count\":3,\"codes\":{\"SYN_REPORT\":3}},\
+             \"EV_ABS\":{\"count\":9,\"codes\":{\"ABS_MT_SLOT\":2,\"ABS_MT_POSITION_X\":2,\
+             \"ABS_MT_POSITION_Y\":2,\"ABS_MT_TRACKING_ID\":3}}}}},\
+             \"gap_threshold_us\":1000000,\"gaps\":[{\"at_us\":20000,\"length_us\":2000000}]}\n"
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::linux::input_event_codes::*;
+use crate::{InputEvent, Packet, TimeVal};
+
+/// Random perturbations of recordings, for testing how robust gesture recognizers are. The
+/// same seed perturbs the same recordings the same way. Zero values do not perturb anything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Jitter {
+    pub seed: u64,
+    /// Largest amount by which a packet is moved earlier or later. Packets stay in order.
+    pub time: Duration,
+    /// Largest amount that is added to or subtracted from `ABS_MT_POSITION_X` and
+    /// `ABS_MT_POSITION_Y` values.
+    pub position: u32,
+    /// Probability of dropping a packet that only moves contacts, from 0 to 1.
+    pub drop: f64,
+}
+
+impl Jitter {
+    pub fn is_empty(&self) -> bool {
+        self.time.is_zero() && self.position == 0 && self.drop <= 0.0
+    }
+}
+
+/// SplitMix64, which is fast and good enough for perturbations. It is implemented here so that
+/// a seed gives the same numbers on every platform and in every version.
+#[derive(Debug)]
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed in `0.0..1.0`.
+    fn fraction(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniformly distributed in `-max..=max`, save for a negligible bias.
+    fn offset(&mut self, max: u64) -> i64 {
+        (self.next() % (2 * max + 1)) as i64 - max as i64
+    }
+}
+
+/// Slot of one device in the recording and as written, which differ after dropping a packet
+/// that selects another slot.
+#[derive(Debug, Default)]
+struct DeviceState {
+    slot: i32,
+    written_slot: i32,
+}
+
+/// Applies [`Jitter`] to packets in the order in which they are written.
+#[derive(Debug)]
+pub(crate) struct Perturber {
+    jitter: Jitter,
+    rng: Rng,
+    devices: HashMap<String, DeviceState>,
+    previous: Duration,
+}
+
+fn is_event(event: &InputEvent, r#type: i32, code: i32) -> bool {
+    i32::from(event.r#type) == r#type && i32::from(event.code) == code
+}
+
+fn is_position(event: &InputEvent) -> bool {
+    is_event(event, EV_ABS, ABS_MT_POSITION_X) || is_event(event, EV_ABS, ABS_MT_POSITION_Y)
+}
+
+/// Whether a packet only moves contacts, without touching, lifting or pressing anything.
+fn is_move(packet: &Packet) -> bool {
+    packet.events.iter().any(is_position)
+        && packet.events.iter().all(|event| {
+            match (i32::from(event.r#type), i32::from(event.code)) {
+                (EV_SYN, SYN_REPORT | SYN_MT_REPORT) | (EV_MSC, _) => true,
+                (EV_ABS, ABS_MT_TRACKING_ID) => false,
+                (EV_ABS, code) => code >= ABS_MT_SLOT,
+                _ => false,
+            }
+        })
+}
+
+impl Perturber {
+    pub(crate) fn new(jitter: &Jitter) -> Self {
+        Perturber {
+            jitter: jitter.clone(),
+            rng: Rng(jitter.seed),
+            devices: HashMap::new(),
+            previous: Duration::ZERO,
+        }
+    }
+
+    /// Perturbs a packet, or drops it.
+    pub(crate) fn apply(&mut self, mut packet: Packet) -> Option<Packet> {
+        if self.jitter.is_empty() {
+            return Some(packet);
+        }
+
+        let state = self.devices.entry(packet.device.clone()).or_default();
+        let slot = state.slot;
+        if let Some(event) = packet
+            .events
+            .iter()
+            .rev()
+            .find(|event| is_event(event, EV_ABS, ABS_MT_SLOT))
+        {
+            state.slot = event.value;
+        }
+        if self.jitter.drop > 0.0 && is_move(&packet) && self.rng.fraction() < self.jitter.drop {
+            return None;
+        }
+        // Select the slot that the dropped packets left selected.
+        if let Some(index) = packet
+            .events
+            .iter()
+            .position(|event| i32::from(event.r#type) == EV_ABS && event.code >= ABS_MT_SLOT as u16)
+        {
+            if slot != state.written_slot && !is_event(&packet.events[index], EV_ABS, ABS_MT_SLOT) {
+                let event = InputEvent {
+                    time: packet.events[index].time.clone(),
+                    r#type: EV_ABS as u16,
+                    code: ABS_MT_SLOT as u16,
+                    value: slot,
+                };
+                packet.events.insert(index, event);
+            }
+            state.written_slot = state.slot;
+        }
+
+        if self.jitter.position > 0 {
+            for event in packet.events.iter_mut().filter(|event| is_position(event)) {
+                let offset = self.rng.offset(self.jitter.position.into());
+                event.value = (i64::from(event.value) + offset) as i32;
+            }
+        }
+
+        if !self.jitter.time.is_zero() {
+            let max = self.jitter.time.as_micros() as u64;
+            let offset = self.rng.offset(max);
+            let time = packet.time().as_micros() as i64 + offset;
+            let time = Duration::from_micros(time.max(0) as u64).max(self.previous);
+            for event in &mut packet.events {
+                event.time = TimeVal::from_duration(time);
+            }
+        }
+        self.previous = packet.time();
+        Some(packet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Jitter, Perturber, Rng};
+    use crate::linux::input_event_codes::*;
+    use crate::{packets, parse_all};
+    use std::time::Duration;
+
+    const INPUT: &str = "\
+[       1.000000] /dev/input/event2: EV_ABS ABS_MT_TRACKING_ID 00000001
+[       1.000000] /dev/input/event2: EV_ABS ABS_MT_POSITION_X 00000100
+[       1.000000] /dev/input/event2: EV_ABS ABS_MT_POSITION_Y 00000100
+[       1.000000] /dev/input/event2: EV_SYN SYN_REPORT 00000000
+[       1.010000] /dev/input/event2: EV_ABS ABS_MT_SLOT 00000001
+[       1.010000] /dev/input/event2: EV_ABS ABS_MT_TRACKING_ID 00000002
+[       1.010000] /dev/input/event2: EV_ABS ABS_MT_POSITION_X 00000200
+[       1.010000] /dev/input/event2: EV_SYN SYN_REPORT 00000000
+[       1.020000] /dev/input/event2: EV_ABS ABS_MT_SLOT 00000000
+[       1.020000] /dev/input/event2: EV_ABS ABS_MT_POSITION_X 00000110
+[       1.020000] /dev/input/event2: EV_SYN SYN_REPORT 00000000
+[       1.030000] /dev/input/event2: EV_ABS ABS_MT_POSITION_Y 00000110
+[       1.030000] /dev/input/event2: EV_SYN SYN_REPORT 00000000
+[       1.040000] /dev/input/event2: EV_ABS ABS_MT_TRACKING_ID ffffffff
+[       1.040000] /dev/input/event2: EV_SYN SYN_REPORT 00000000
+[       1.040000] /dev/input/event0: EV_KEY KEY_A DOWN
+[       1.040000] /dev/input/event0: EV_SYN SYN_REPORT 00000000
+";
+
+    /// Type, code and value of each event of a packet.
+    type Events = Vec<(i32, i32, i32)>;
+
+    /// Time in microseconds and events of each perturbed packet.
+    fn perturb(input: &str, jitter: &Jitter) -> Vec<(u128, Events)> {
+        let mut perturber = Perturber::new(jitter);
+        packets(parse_all(&mut input.as_bytes()), None)
+            .filter_map(|packet| perturber.apply(packet.unwrap()))
+            .map(|packet| {
+                let events = packet
+                    .events
+                    .iter()
+                    .map(|event| (event.r#type.into(), event.code.into(), event.value))
+                    .collect();
+                (packet.time().as_micros(), events)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rng() {
+        // The first outputs of SplitMix64 seeded with 1234567.
+        let mut rng = Rng(1234567);
+        assert_eq!(rng.next(), 6457827717110365317);
+        assert_eq!(rng.next(), 3203168211198807973);
+        let mut rng = Rng(0);
+        for _ in 0..1000 {
+            assert!((-3..=3).contains(&rng.offset(3)));
+            assert!((0.0..1.0).contains(&rng.fraction()));
+        }
+    }
+
+    #[test]
+    fn perturb_packets() {
+        let unchanged = perturb(INPUT, &Jitter::default());
+        assert_eq!(unchanged.len(), 6);
+
+        let jitter = Jitter {
+            seed: 7,
+            time: Duration::from_millis(4),
+            position: 5,
+            drop: 0.0,
+        };
+        let perturbed = perturb(INPUT, &jitter);
+        assert_eq!(perturbed, perturb(INPUT, &jitter));
+        assert_ne!(perturbed, perturb(INPUT, &Jitter { seed: 8, ..jitter }));
+        assert_eq!(perturbed.len(), unchanged.len());
+        for ((time, events), (original_time, original_events)) in perturbed.iter().zip(&unchanged) {
+            assert!(time.abs_diff(*original_time) <= 4000);
+            for (&(r#type, code, value), &(_, _, original_value)) in
+                events.iter().zip(original_events)
+            {
+                let noise = if code == ABS_MT_POSITION_X || code == ABS_MT_POSITION_Y {
+                    5
+                } else {
+                    0
+                };
+                assert!(value.abs_diff(original_value) <= noise, "{:04x}", r#type);
+            }
+        }
+        assert!(perturbed.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+
+        // Only the two packets that move the first contact can be dropped. The slot that the
+        // first of them selects is selected again for the packet that lifts the contact.
+        let drop_all = Jitter {
+            drop: 1.0,
+            ..Jitter::default()
+        };
+        let dropped = perturb(INPUT, &drop_all);
+        assert_eq!(dropped.len(), 4);
+        assert_eq!(
+            dropped[2],
+            (
+                1040000,
+                vec![
+                    (EV_ABS, ABS_MT_SLOT, 0),
+                    (EV_ABS, ABS_MT_TRACKING_ID, -1),
+                    (EV_SYN, SYN_REPORT, 0),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn drop_before_other_packet() {
+        // The packet that only reports a timestamp does not select a slot, so slot 0 has to be
+        // selected again for the packet that lifts the contact.
+        let input = "\
+[       1.000000] /dev/input/event2: EV_ABS ABS_MT_TRACKING_ID 00000001
+[       1.000000] /dev/input/event2: EV_SYN SYN_REPORT 00000000
+[       1.010000] /dev/input/event2: EV_ABS ABS_MT_SLOT 00000001
+[       1.010000] /dev/input/event2: EV_ABS ABS_MT_TRACKING_ID 00000002
+[       1.010000] /dev/input/event2: EV_SYN SYN_REPORT 00000000
+[       1.020000] /dev/input/event2: EV_ABS ABS_MT_SLOT 00000000
+[       1.020000] /dev/input/event2: EV_ABS ABS_MT_POSITION_X 00000110
+[       1.020000] /dev/input/event2: EV_SYN SYN_REPORT 00000000
+[       1.030000] /dev/input/event2: EV_MSC MSC_TIMESTAMP 00002710
+[       1.030000] /dev/input/event2: EV_SYN SYN_REPORT 00000000
+[       1.040000] /dev/input/event2: EV_ABS ABS_MT_TRACKING_ID ffffffff
+[       1.040000] /dev/input/event2: EV_SYN SYN_REPORT 00000000
+";
+        let drop_all = Jitter {
+            drop: 1.0,
+            ..Jitter::default()
+        };
+        let dropped = perturb(input, &drop_all);
+        assert_eq!(dropped.len(), 4);
+        assert_eq!(
+            dropped[3],
+            (
+                1040000,
+                vec![
+                    (EV_ABS, ABS_MT_SLOT, 0),
+                    (EV_ABS, ABS_MT_TRACKING_ID, -1),
+                    (EV_SYN, SYN_REPORT, 0),
+                ]
+            )
+        );
+    }
+}
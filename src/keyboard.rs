@@ -0,0 +1,582 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::mem;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::linux::input_event_codes::*;
+use crate::{is_syn_report, Error, Packet};
+
+/// Modifiers that have to be held while pressing a key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub altgr: bool,
+}
+
+/// Modifier keys that are held on a device, on either side of the keyboard except for alt and
+/// altgr.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeldModifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    /// The left alt key.
+    pub alt: bool,
+    /// The right alt key.
+    pub altgr: bool,
+    pub meta: bool,
+}
+
+/// A row of keys and the characters they produce without and with shift. NUL marks keys that
+/// do not produce a character directly, like dead keys.
+struct Row {
+    keys: &'static [i32],
+    plain: &'static str,
+    shift: &'static str,
+}
+
+const US_ROWS: &[Row] = &[
+    Row {
+        keys: &[
+            KEY_GRAVE, KEY_1, KEY_2, KEY_3, KEY_4, KEY_5, KEY_6, KEY_7, KEY_8, KEY_9, KEY_0,
+            KEY_MINUS, KEY_EQUAL,
+        ],
+        plain: "`1234567890-=",
+        shift: "~!@#$%^&*()_+",
+    },
+    Row {
+        keys: &[
+            KEY_Q,
+            KEY_W,
+            KEY_E,
+            KEY_R,
+            KEY_T,
+            KEY_Y,
+            KEY_U,
+            KEY_I,
+            KEY_O,
+            KEY_P,
+            KEY_LEFTBRACE,
+            KEY_RIGHTBRACE,
+            KEY_BACKSLASH,
+        ],
+        plain: "qwertyuiop[]\\",
+        shift: "QWERTYUIOP{}|",
+    },
+    Row {
+        keys: &[
+            KEY_A,
+            KEY_S,
+            KEY_D,
+            KEY_F,
+            KEY_G,
+            KEY_H,
+            KEY_J,
+            KEY_K,
+            KEY_L,
+            KEY_SEMICOLON,
+            KEY_APOSTROPHE,
+        ],
+        plain: "asdfghjkl;'",
+        shift: "ASDFGHJKL:\"",
+    },
+    Row {
+        keys: &[
+            KEY_Z, KEY_X, KEY_C, KEY_V, KEY_B, KEY_N, KEY_M, KEY_COMMA, KEY_DOT, KEY_SLASH,
+        ],
+        plain: "zxcvbnm,./",
+        shift: "ZXCVBNM<>?",
+    },
+];
+
+const DE_ROWS: &[Row] = &[
+    Row {
+        keys: &[
+            KEY_GRAVE, KEY_1, KEY_2, KEY_3, KEY_4, KEY_5, KEY_6, KEY_7, KEY_8, KEY_9, KEY_0,
+            KEY_MINUS, KEY_EQUAL,
+        ],
+        plain: "\x001234567890ß\x00",
+        shift: "°!\"§$%&/()=?\x00",
+    },
+    Row {
+        keys: &[
+            KEY_Q,
+            KEY_W,
+            KEY_E,
+            KEY_R,
+            KEY_T,
+            KEY_Y,
+            KEY_U,
+            KEY_I,
+            KEY_O,
+            KEY_P,
+            KEY_LEFTBRACE,
+            KEY_RIGHTBRACE,
+        ],
+        plain: "qwertzuiopü+",
+        shift: "QWERTZUIOPÜ*",
+    },
+    Row {
+        keys: &[
+            KEY_A,
+            KEY_S,
+            KEY_D,
+            KEY_F,
+            KEY_G,
+            KEY_H,
+            KEY_J,
+            KEY_K,
+            KEY_L,
+            KEY_SEMICOLON,
+            KEY_APOSTROPHE,
+            KEY_BACKSLASH,
+        ],
+        plain: "asdfghjklöä#",
+        shift: "ASDFGHJKLÖÄ'",
+    },
+    Row {
+        keys: &[
+            KEY_102ND, KEY_Z, KEY_X, KEY_C, KEY_V, KEY_B, KEY_N, KEY_M, KEY_COMMA, KEY_DOT,
+            KEY_SLASH,
+        ],
+        plain: "<yxcvbnm,.-",
+        shift: ">YXCVBNM;:_",
+    },
+];
+
+const DE_ALTGR: &[(char, i32)] = &[
+    ('²', KEY_2),
+    ('³', KEY_3),
+    ('{', KEY_7),
+    ('[', KEY_8),
+    (']', KEY_9),
+    ('}', KEY_0),
+    ('\\', KEY_MINUS),
+    ('@', KEY_Q),
+    ('€', KEY_E),
+    ('~', KEY_RIGHTBRACE),
+    ('|', KEY_102ND),
+    ('µ', KEY_M),
+];
+
+/// Keys that produce the same character in every layout.
+const COMMON: &[(char, i32)] = &[(' ', KEY_SPACE), ('\t', KEY_TAB), ('\n', KEY_ENTER)];
+
+/// Keyboard layout that the device is configured with on the receiving side.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// US QWERTY.
+    #[default]
+    Us,
+    /// German QWERTZ, without the dead keys `^`, `´` and `` ` ``.
+    De,
+}
+
+impl Layout {
+    /// The key and modifiers that produce a character, if any.
+    pub fn key(self, c: char) -> Option<(i32, Modifiers)> {
+        if let Some(&(_, key)) = COMMON.iter().find(|&&(common, _)| common == c) {
+            return Some((key, Modifiers::default()));
+        }
+        let (rows, altgr) = match self {
+            Layout::Us => (US_ROWS, &[][..]),
+            Layout::De => (DE_ROWS, DE_ALTGR),
+        };
+        for row in rows {
+            for (shift, chars) in [(false, row.plain), (true, row.shift)] {
+                if let Some((_, &key)) = chars
+                    .chars()
+                    .zip(row.keys)
+                    .find(|&(k, _)| k == c && k != '\x00')
+                {
+                    let modifiers = Modifiers {
+                        shift,
+                        altgr: false,
+                    };
+                    return Some((key, modifiers));
+                }
+            }
+        }
+        altgr.iter().find(|&&(k, _)| k == c).map(|&(_, key)| {
+            let modifiers = Modifiers {
+                shift: false,
+                altgr: true,
+            };
+            (key, modifiers)
+        })
+    }
+}
+
+impl FromStr for Layout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "us" => Ok(Layout::Us),
+            "de" => Ok(Layout::De),
+            _ => Err(format!("unknown keyboard layout {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Layout::Us => "us",
+            Layout::De => "de",
+        })
+    }
+}
+
+/// Keys and switches of one device, tracked from its events.
+#[derive(Debug, Clone, Default)]
+pub struct KeyState {
+    keys: BTreeSet<u16>,
+    switches: BTreeMap<u16, i32>,
+}
+
+impl KeyState {
+    /// Updates the state with an event. Returns whether the event changed the state, which a
+    /// press of a key that is down and a release of a key that is up do not. A repeat of a key
+    /// that is up presses it, as if the press had been missed.
+    pub fn event(&mut self, r#type: u16, code: u16, value: i32) -> bool {
+        match i32::from(r#type) {
+            EV_KEY if value == 0 => self.keys.remove(&code),
+            EV_KEY => self.keys.insert(code),
+            EV_SW => self.switches.insert(code, value) != Some(value),
+            _ => false,
+        }
+    }
+
+    /// Keys that are down, ordered by code.
+    pub fn pressed(&self) -> impl Iterator<Item = u16> + '_ {
+        self.keys.iter().copied()
+    }
+
+    pub fn is_pressed(&self, code: u16) -> bool {
+        self.keys.contains(&code)
+    }
+
+    /// Value of a switch, if it has reported one.
+    pub fn switch(&self, code: u16) -> Option<i32> {
+        self.switches.get(&code).copied()
+    }
+
+    /// Modifier keys that are down.
+    pub fn modifiers(&self) -> HeldModifiers {
+        let any = |keys: &[i32]| keys.iter().any(|&key| self.is_pressed(key as u16));
+        HeldModifiers {
+            shift: any(&[KEY_LEFTSHIFT, KEY_RIGHTSHIFT]),
+            ctrl: any(&[KEY_LEFTCTRL, KEY_RIGHTCTRL]),
+            alt: any(&[KEY_LEFTALT]),
+            altgr: any(&[KEY_RIGHTALT]),
+            meta: any(&[KEY_LEFTMETA, KEY_RIGHTMETA]),
+        }
+    }
+}
+
+/// Keys of each device while writing packets, to drop presses of keys that are already down
+/// and to release the keys that are still down at the end.
+#[derive(Debug, Default)]
+pub(crate) struct HeldKeys {
+    /// State of each device, with the time of its last packet.
+    devices: BTreeMap<String, (KeyState, Duration)>,
+}
+
+impl HeldKeys {
+    /// Removes presses of keys that are already down from a packet. Like with filters, the
+    /// whole packet is dropped if only its `SYN_REPORT` event is left.
+    pub(crate) fn apply(&mut self, mut packet: Packet) -> Option<Packet> {
+        let time = packet.time();
+        let (state, last) = self.devices.entry(packet.device.clone()).or_default();
+        *last = time;
+        let len = packet.events.len();
+        packet.events.retain(|event| {
+            let repeated = i32::from(event.r#type) == EV_KEY
+                && event.value == 1
+                && state.is_pressed(event.code);
+            state.event(event.r#type, event.code, event.value);
+            !repeated
+        });
+        if packet.events.len() < len && packet.events.iter().all(is_syn_report) {
+            None
+        } else {
+            Some(packet)
+        }
+    }
+
+    /// Packets that release the keys that are still down, in reverse order of their codes, at
+    /// the time of the last packet of each device.
+    pub(crate) fn release(&mut self) -> Vec<Packet> {
+        mem::take(&mut self.devices)
+            .into_iter()
+            .filter_map(|(device, (state, time))| {
+                let keys: Vec<_> = state.pressed().collect();
+                if keys.is_empty() {
+                    return None;
+                }
+                let events = keys.into_iter().rev().map(|key| (EV_KEY, key.into(), 0));
+                Some(Packet::new(&device, time, events))
+            })
+            .collect()
+    }
+}
+
+/// Turns text into key presses for one device.
+#[derive(Debug)]
+pub struct Typist {
+    device: String,
+    layout: Layout,
+    /// Time from one character to the next. Each key is held for half of it.
+    pub delay: Duration,
+}
+
+impl Typist {
+    pub fn new(device: &str, layout: Layout) -> Self {
+        Typist {
+            device: device.to_string(),
+            layout,
+            delay: Duration::from_millis(100),
+        }
+    }
+
+    /// Generates the packets that type `text`, timestamped relative to the first key press.
+    /// Fails with all characters that the layout cannot produce, without generating anything.
+    pub(crate) fn generate(&self, text: &str) -> Result<Vec<Packet>, Error> {
+        let mut keys = vec![];
+        let mut missing = vec![];
+        for c in text.chars() {
+            match self.layout.key(c) {
+                Some(key) => keys.push(key),
+                None if !missing.contains(&c) => missing.push(c),
+                None => {}
+            }
+        }
+        if !missing.is_empty() {
+            let missing: Vec<_> = missing
+                .iter()
+                .map(|c| format!("{:?} (U+{:04X})", c, u32::from(*c)))
+                .collect();
+            return Err(Error::Format(format!(
+                "cannot type {} with the {} layout",
+                missing.join(", "),
+                self.layout
+            )));
+        }
+
+        let mut result = vec![];
+        let mut time = Duration::ZERO;
+        for (key, modifiers) in keys {
+            let mut pressed = vec![];
+            if modifiers.shift {
+                pressed.push(KEY_LEFTSHIFT);
+            }
+            if modifiers.altgr {
+                pressed.push(KEY_RIGHTALT);
+            }
+            pressed.push(key);
+            let released = pressed.iter().rev().copied().collect::<Vec<_>>();
+            result.push(self.packet(time, &pressed, 1));
+            result.push(self.packet(time + self.delay / 2, &released, 0));
+            time += self.delay;
+        }
+        Ok(result)
+    }
+
+    fn packet(&self, time: Duration, keys: &[i32], value: i32) -> Packet {
+        Packet::new(
+            &self.device,
+            time,
+            keys.iter().map(|&key| (EV_KEY, key, value)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HeldKeys, HeldModifiers, KeyState, Layout, Modifiers, Typist};
+    use crate::linux::input_event_codes::*;
+    use crate::{packets, parse_all};
+    use std::time::Duration;
+
+    const SHIFT: Modifiers = Modifiers {
+        shift: true,
+        altgr: false,
+    };
+    const ALTGR: Modifiers = Modifiers {
+        shift: false,
+        altgr: true,
+    };
+
+    #[test]
+    fn key() {
+        assert_eq!(Layout::Us.key('a'), Some((KEY_A, Modifiers::default())));
+        assert_eq!(Layout::Us.key('A'), Some((KEY_A, SHIFT)));
+        assert_eq!(Layout::Us.key('@'), Some((KEY_2, SHIFT)));
+        assert_eq!(
+            Layout::Us.key('\n'),
+            Some((KEY_ENTER, Modifiers::default()))
+        );
+        assert_eq!(Layout::Us.key('ä'), None);
+        assert_eq!(Layout::De.key('z'), Some((KEY_Y, Modifiers::default())));
+        assert_eq!(Layout::De.key('@'), Some((KEY_Q, ALTGR)));
+        assert_eq!(Layout::De.key('Ä'), Some((KEY_APOSTROPHE, SHIFT)));
+        assert_eq!(Layout::De.key('^'), None);
+        assert_eq!(Layout::De.key('\0'), None);
+    }
+
+    #[test]
+    fn generate() {
+        let mut typist = Typist::new("/dev/input/event0", Layout::De);
+        typist.delay = Duration::from_millis(40);
+        let result = typist
+            .generate("a@")
+            .unwrap()
+            .iter()
+            .map(|packet| {
+                let events: Vec<_> = packet
+                    .events
+                    .iter()
+                    .map(|event| (event.code as i32, event.value))
+                    .collect();
+                (packet.time().as_millis(), events)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            result,
+            [
+                (0, vec![(KEY_A, 1), (SYN_REPORT, 0)]),
+                (20, vec![(KEY_A, 0), (SYN_REPORT, 0)]),
+                (40, vec![(KEY_RIGHTALT, 1), (KEY_Q, 1), (SYN_REPORT, 0)]),
+                (60, vec![(KEY_Q, 0), (KEY_RIGHTALT, 0), (SYN_REPORT, 0)]),
+            ]
+        );
+
+        let error = Typist::new("/dev/input/event0", Layout::Us)
+            .generate("née€é")
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "cannot type 'é' (U+00E9), '€' (U+20AC) with the us layout"
+        );
+    }
+
+    #[test]
+    fn key_state() {
+        // Shift and a held long enough to repeat on a laptop keyboard, which starts with the
+        // release of the enter key that ran getevent, and then the lid closing.
+        let input = "\
+[    4005.207715] /dev/input/event3: EV_MSC       MSC_SCAN             0000001c
+[    4005.207715] /dev/input/event3: EV_KEY       KEY_ENTER            UP
+[    4005.207715] /dev/input/event3: EV_SYN       SYN_REPORT           00000000
+[    4007.094101] /dev/input/event3: EV_MSC       MSC_SCAN             0000002a
+[    4007.094101] /dev/input/event3: EV_KEY       KEY_LEFTSHIFT        DOWN
+[    4007.094101] /dev/input/event3: EV_SYN       SYN_REPORT           00000000
+[    4007.230459] /dev/input/event3: EV_MSC       MSC_SCAN             0000001e
+[    4007.230459] /dev/input/event3: EV_KEY       KEY_A                DOWN
+[    4007.230459] /dev/input/event3: EV_SYN       SYN_REPORT           00000000
+[    4007.731248] /dev/input/event3: EV_KEY       KEY_A                REPEAT
+[    4007.731248] /dev/input/event3: EV_SYN       SYN_REPORT           00000001
+[    4007.764582] /dev/input/event3: EV_KEY       KEY_LEFTSHIFT        REPEAT
+[    4007.764582] /dev/input/event3: EV_SYN       SYN_REPORT           00000001
+[    4007.801935] /dev/input/event3: EV_MSC       MSC_SCAN             0000001e
+[    4007.801935] /dev/input/event3: EV_KEY       KEY_A                UP
+[    4007.801935] /dev/input/event3: EV_SYN       SYN_REPORT           00000000
+[    4007.901214] /dev/input/event3: EV_MSC       MSC_SCAN             0000002a
+[    4007.901214] /dev/input/event3: EV_KEY       KEY_LEFTSHIFT        UP
+[    4007.901214] /dev/input/event3: EV_SYN       SYN_REPORT           00000000
+[    4012.505170] /dev/input/event0: EV_SW        SW_LID               00000001
+[    4012.505170] /dev/input/event0: EV_SYN       SYN_REPORT           00000000
+";
+        let mut state = KeyState::default();
+        let result: Vec<_> = packets(parse_all(&mut input.as_bytes()), None)
+            .map(|packet| {
+                let packet = packet.unwrap();
+                let changed: Vec<_> = packet
+                    .events
+                    .iter()
+                    .map(|event| state.event(event.r#type, event.code, event.value))
+                    .collect();
+                let pressed: Vec<_> = state.pressed().map(i32::from).collect();
+                (changed, pressed, state.modifiers().shift)
+            })
+            .collect();
+        assert_eq!(
+            result,
+            [
+                (vec![false, false, false], vec![], false),
+                (vec![false, true, false], vec![KEY_LEFTSHIFT], true),
+                (vec![false, true, false], vec![KEY_A, KEY_LEFTSHIFT], true),
+                (vec![false, false], vec![KEY_A, KEY_LEFTSHIFT], true),
+                (vec![false, false], vec![KEY_A, KEY_LEFTSHIFT], true),
+                (vec![false, true, false], vec![KEY_LEFTSHIFT], true),
+                (vec![false, true, false], vec![], false),
+                (vec![true, false], vec![], false),
+            ]
+        );
+        assert_eq!(state.switch(SW_LID as u16), Some(1));
+        assert_eq!(state.switch(SW_HEADPHONE_INSERT as u16), None);
+        assert!(!state.event(EV_SW as u16, SW_LID as u16, 1));
+        assert!(state.event(EV_KEY as u16, KEY_RIGHTALT as u16, 2));
+        assert_eq!(
+            state.modifiers(),
+            HeldModifiers {
+                altgr: true,
+                ..HeldModifiers::default()
+            }
+        );
+    }
+
+    #[test]
+    fn held_keys() {
+        let input = "\
+[       1.000000] /dev/input/event0: EV_KEY KEY_A DOWN
+[       1.000000] /dev/input/event0: EV_SYN SYN_REPORT 00000000
+[       2.000000] /dev/input/event0: EV_KEY KEY_A DOWN
+[       2.000000] /dev/input/event0: EV_SYN SYN_REPORT 00000000
+[       3.000000] /dev/input/event0: EV_KEY KEY_A DOWN
+[       3.000000] /dev/input/event0: EV_KEY KEY_B DOWN
+[       3.000000] /dev/input/event0: EV_SYN SYN_REPORT 00000000
+[       4.000000] /dev/input/event0: EV_KEY KEY_A REPEAT
+[       4.000000] /dev/input/event0: EV_SYN SYN_REPORT 00000001
+[       5.000000] /dev/input/event1: EV_KEY KEY_C DOWN
+[       5.000000] /dev/input/event1: EV_KEY KEY_C UP
+[       5.000000] /dev/input/event1: EV_SYN SYN_REPORT 00000000
+";
+        let codes = |packet: &crate::Packet| -> Vec<_> {
+            packet
+                .events
+                .iter()
+                .map(|event| (i32::from(event.code), event.value))
+                .collect()
+        };
+        let mut held_keys = HeldKeys::default();
+        let result: Vec<_> = packets(parse_all(&mut input.as_bytes()), None)
+            .filter_map(|packet| held_keys.apply(packet.unwrap()))
+            .map(|packet| (packet.time().as_secs(), codes(&packet)))
+            .collect();
+        assert_eq!(
+            result,
+            [
+                (1, vec![(KEY_A, 1), (SYN_REPORT, 0)]),
+                (3, vec![(KEY_B, 1), (SYN_REPORT, 0)]),
+                (4, vec![(KEY_A, 2), (SYN_REPORT, 1)]),
+                (5, vec![(KEY_C, 1), (KEY_C, 0), (SYN_REPORT, 0)]),
+            ]
+        );
+        let released: Vec<_> = held_keys
+            .release()
+            .into_iter()
+            .map(|packet| (packet.time().as_secs(), codes(&packet), packet.device))
+            .collect();
+        assert_eq!(
+            released,
+            [(
+                4,
+                vec![(KEY_B, 0), (KEY_A, 0), (SYN_REPORT, 0)],
+                "/dev/input/event0".to_string(),
+            )]
+        );
+        assert!(held_keys.release().is_empty());
+    }
+}
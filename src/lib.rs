@@ -1,21 +1,74 @@
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry, BTreeMap, HashMap};
 use std::fs::File;
 use std::io::{self, BufRead, Write};
-use std::time::{Duration, SystemTime};
-use std::{iter, num, str, thread};
+use std::time::{Duration, Instant};
+use std::{fmt, iter, mem, num, str, thread};
 
+use keyboard::HeldKeys;
 use linux::input_event_codes;
+use merge::Merge;
 
+mod decompile;
+mod device;
+mod expression;
+mod filter;
 mod gen;
+mod gesture;
+mod info;
+mod jitter;
+mod keyboard;
 pub mod linux;
+mod merge;
+mod mt;
+mod protocol;
+mod report;
+mod script;
+mod svg;
+mod validate;
 
-#[derive(Debug)]
+pub use device::DeviceInfo;
+pub use filter::{Filter, Rule};
+pub use gen::ParseError;
+pub use gesture::{Contact, Gesture, GestureGenerator, Path, Point};
+pub use info::{DeviceStats, RecordingInfo};
+pub use jitter::Jitter;
+pub use keyboard::{HeldModifiers, KeyState, Layout, Modifiers, Typist};
+pub use mt::{MtState, Touch};
+pub use protocol::MtProtocol;
+pub use report::TimingReport;
+pub use script::Script;
+pub use validate::Problem;
+
+#[derive(Debug, Clone)]
 struct TimeVal {
     sec: i64,
     usec: i64,
 }
 
 impl TimeVal {
+    // `time_t` and `c_long` are 32 bits wide on 32-bit Android targets.
+    #[allow(clippy::unnecessary_cast)]
+    fn now(clock: libc::clockid_t) -> Self {
+        let mut timespec = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        if unsafe { libc::clock_gettime(clock, &mut timespec) } != 0 {
+            panic!("{}", io::Error::last_os_error());
+        }
+        TimeVal {
+            sec: timespec.tv_sec as i64,
+            usec: timespec.tv_nsec as i64 / 1000,
+        }
+    }
+
+    fn from_duration(duration: Duration) -> Self {
+        TimeVal {
+            sec: duration.as_secs().try_into().unwrap(),
+            usec: duration.subsec_micros().into(),
+        }
+    }
+
     fn to_duration(&self) -> Duration {
         Duration::new(
             self.sec.try_into().unwrap(),
@@ -24,7 +77,7 @@ impl TimeVal {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct InputEvent {
     time: TimeVal,
     r#type: u16,
@@ -45,11 +98,38 @@ impl InputEvent {
     }
 }
 
-fn parse_event(line: &str, options: &Options) -> Result<(Option<String>, InputEvent), Error> {
+/// Formats the event like `getevent -l` does, except that names are never truncated.
+impl fmt::Display for InputEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(name) = gen::get_type_name(self.r#type) {
+            write!(f, "{:<12}", name)?;
+        } else {
+            write!(f, "{:04x}        ", self.r#type)?;
+        }
+        if let Some(name) = gen::get_code_name(self.r#type, self.code) {
+            write!(f, " {:<20}", name)?;
+        } else {
+            write!(f, " {:04x}                ", self.code)?;
+        }
+        if let Some(name) = gen::get_value_name(self.r#type, self.code, self.value) {
+            write!(f, " {}", name)
+        } else if i32::from(self.r#type) == input_event_codes::EV_REP && self.value >= 0 {
+            write!(f, " {}ms", self.value)
+        } else {
+            write!(f, " {:08x}", self.value)
+        }
+    }
+}
+
+fn parse_event(
+    line: &str,
+    options: &Options,
+    radix: Radix,
+) -> Result<(Option<String>, InputEvent), Error> {
     let line_saved = line;
     let (line, sec, usec) = if options.get_time {
         let bytes = line.as_bytes();
-        if let Some(&c) = bytes.get(0) {
+        if let Some(&c) = bytes.first() {
             if c != b'[' {
                 return Err(Error::Format(format!(
                     "missing `[` for line: {:?}",
@@ -72,27 +152,31 @@ fn parse_event(line: &str, options: &Options) -> Result<(Option<String>, InputEv
             )));
         };
         if let Some((first, second)) = time.split_once('.') {
-            (
-                line,
-                match first.trim_start().parse() {
-                    Ok(first) => first,
-                    Err(error) => {
-                        return Err(Error::ParseInt(
-                            error,
-                            format!("parsing time sec field for line: {:?}", line_saved),
-                        ))
-                    }
-                },
-                match second.parse() {
-                    Ok(second) => second,
-                    Err(error) => {
-                        return Err(Error::ParseInt(
-                            error,
-                            format!("parsing time usec field for line: {:?}", line_saved),
-                        ))
-                    }
-                },
-            )
+            let sec = match first.trim_start().parse() {
+                Ok(first) => first,
+                Err(error) => {
+                    return Err(Error::ParseInt(
+                        error,
+                        format!("parsing time sec field for line: {:?}", line_saved),
+                    ))
+                }
+            };
+            let usec: u32 = match second.parse() {
+                Ok(second) => second,
+                Err(error) => {
+                    return Err(Error::ParseInt(
+                        error,
+                        format!("parsing time usec field for line: {:?}", line_saved),
+                    ))
+                }
+            };
+            if sec < 0 || usec >= 1_000_000 {
+                return Err(Error::Time(format!(
+                    "timestamp out of range for line: {:?}",
+                    line_saved
+                )));
+            }
+            (line, sec, usec.into())
         } else {
             return Err(Error::Format(format!(
                 "missing `.` in timestamp field for line: {:?}",
@@ -118,10 +202,10 @@ fn parse_event(line: &str, options: &Options) -> Result<(Option<String>, InputEv
 
     let mut splits = line.split(' ').filter(|s| !s.is_empty());
     let r#type = if let Some(s) = splits.next() {
-        match gen::get_type_value(s) {
+        match gen::get_type_value(s, radix) {
             Ok(value) => value,
             Err(error) => {
-                return Err(Error::ParseInt(
+                return Err(Error::Parse(
                     error,
                     format!("parsing type field for line: {:?}", line_saved),
                 ))
@@ -135,10 +219,10 @@ fn parse_event(line: &str, options: &Options) -> Result<(Option<String>, InputEv
     };
 
     let code = if let Some(s) = splits.next() {
-        match gen::get_code_value(r#type, s) {
+        match gen::get_code_value(r#type, s, radix) {
             Ok(value) => value,
             Err(error) => {
-                return Err(Error::ParseInt(
+                return Err(Error::Parse(
                     error,
                     format!("parsing code field for line: {:?}", line_saved),
                 ))
@@ -152,10 +236,10 @@ fn parse_event(line: &str, options: &Options) -> Result<(Option<String>, InputEv
     };
 
     let value = if let Some(s) = splits.next() {
-        match gen::get_value_value(r#type, code, s) {
+        match gen::get_value_value(r#type, code, s, radix) {
             Ok(value) => value,
             Err(error) => {
-                return Err(Error::ParseInt(
+                return Err(Error::Parse(
                     error,
                     format!("parsing value field for line: {:?}", line_saved),
                 ))
@@ -184,7 +268,7 @@ fn write_event(device: &mut impl Write, event: &InputEvent) -> Result<(), io::Er
 }
 
 fn get_options(line: &str) -> Result<Options, Error> {
-    let get_time = if let Some(&c) = line.as_bytes().get(0) {
+    let get_time = if let Some(&c) = line.as_bytes().first() {
         c == b'['
     } else {
         return Err(Error::Format("empty line".to_string()));
@@ -200,11 +284,7 @@ fn get_options(line: &str) -> Result<Options, Error> {
         line
     };
 
-    let print_device = if let Some(_) = line.split_once(": ") {
-        true
-    } else {
-        false
-    };
+    let print_device = line.split_once(": ").is_some();
 
     Ok(Options {
         get_time,
@@ -219,101 +299,772 @@ struct Options {
 }
 
 #[derive(Debug)]
-enum Error {
+pub enum Error {
     Format(String),
     IO(io::Error),
     Utf8(str::Utf8Error),
     ParseInt(num::ParseIntError, String),
+    /// A type, code or value that could not be parsed.
+    Parse(ParseError, String),
+    Time(String),
+    Line(usize, Box<Error>),
+    /// An error in an included file.
+    Include(String, Box<Error>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Format(message) => write!(f, "{}", message),
+            Error::IO(error) => write!(f, "{}", error),
+            Error::Utf8(error) => write!(f, "{}", error),
+            Error::ParseInt(error, context) => write!(f, "{} ({})", error, context),
+            Error::Parse(error, context) => write!(f, "{} ({})", error, context),
+            Error::Time(message) => write!(f, "{}", message),
+            Error::Line(line, error) => write!(f, "line {}: {}", line, error),
+            Error::Include(path, error) => write!(f, "{}: {}", path, error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::IO(error)
+    }
 }
 
+type ParseResult = Result<(Option<String>, InputEvent), Error>;
+
+/// Parses every line of the input, paired with its line number.
 fn parse_all(
-    reader: &mut impl BufRead,
-) -> Box<dyn Iterator<Item = Result<(Option<String>, InputEvent), Error>> + '_> {
-    let mut lines = reader.lines();
-    let line = if let Some(result) = lines.next() {
-        match result {
-            Ok(line) => line,
-            Err(error) => return Box::new(iter::once(Err(Error::IO(error)))),
+    reader: &mut (impl BufRead + ?Sized),
+) -> Box<dyn Iterator<Item = (usize, ParseResult)> + '_> {
+    parse_paced(reader, Duration::ZERO, Radix::Hex)
+}
+
+/// Removes a comment that starts with `#` after whitespace from the end of a line.
+fn strip_comment(line: &str) -> &str {
+    line.char_indices()
+        .find(|&(index, c)| c == '#' && line[..index].ends_with(char::is_whitespace))
+        .map_or(line, |(index, _)| &line[..index])
+        .trim_end()
+}
+
+/// Parses every line of the input, paired with its line number. Blank lines and comments,
+/// which start with `#`, are skipped. Events of input without timestamps are timestamped from
+/// zero, `interval` apart from one `SYN_REPORT` to the next, and `# sleep <DURATION>` lines
+/// pause for the duration. Numbers without a `0x` or `0d` prefix are read in `radix` until a
+/// `# radix hex` or `# radix decimal` line selects another one.
+fn parse_paced(
+    reader: &mut (impl BufRead + ?Sized),
+    interval: Duration,
+    mut radix: Radix,
+) -> Box<dyn Iterator<Item = (usize, ParseResult)> + '_> {
+    let mut options: Option<Options> = None;
+    let mut time = Duration::ZERO;
+    // Line of the first `# sleep` before the first event, an error if the events have timestamps.
+    let mut early_sleep = None;
+    let sleep_error =
+        || Error::Format("`# sleep` is only allowed in input without timestamps".to_string());
+    Box::new(
+        reader
+            .lines()
+            .enumerate()
+            .filter_map(move |(index, result)| {
+                let line = match result {
+                    Ok(line) => line,
+                    Err(error) => return Some((index + 1, Err(Error::IO(error)))),
+                };
+                if let Some(duration) = line.strip_prefix("# sleep ") {
+                    let result = if options.as_ref().is_some_and(|options| options.get_time) {
+                        Err(sleep_error())
+                    } else {
+                        match parse_duration(duration.trim()) {
+                            Ok(duration) => {
+                                if options.is_none() {
+                                    early_sleep.get_or_insert(index + 1);
+                                }
+                                time += duration;
+                                return None;
+                            }
+                            Err(error) => Err(Error::Format(error)),
+                        }
+                    };
+                    return Some((index + 1, result));
+                }
+                if let Some(name) = line.strip_prefix("# radix ") {
+                    match strip_comment(name).trim().parse() {
+                        Ok(new) => radix = new,
+                        Err(error) => return Some((index + 1, Err(Error::Format(error)))),
+                    }
+                    return None;
+                }
+                if line.trim_start().starts_with('#') || line.trim().is_empty() {
+                    return None;
+                }
+                let line = strip_comment(&line);
+                let options = match &mut options {
+                    Some(options) => options,
+                    None => match (get_options(line), early_sleep) {
+                        (Ok(new), Some(line)) if new.get_time => {
+                            return Some((line, Err(sleep_error())))
+                        }
+                        (Ok(new), _) => options.insert(new),
+                        (Err(error), _) => return Some((index + 1, Err(error))),
+                    },
+                };
+                let result = parse_event(line, options, radix).map(|(device, mut event)| {
+                    if !options.get_time {
+                        event.time = TimeVal::from_duration(time);
+                        if is_syn_report(&event) {
+                            time += interval;
+                        }
+                    }
+                    (device, event)
+                });
+                Some((index + 1, result))
+            }),
+    )
+}
+
+/// How the `time` field of written events is filled in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Timestamp {
+    /// Write zero timestamps.
+    #[default]
+    Zero,
+    /// Stamp each packet with `CLOCK_MONOTONIC` at the moment it is written.
+    Monotonic,
+    /// Stamp each packet with `CLOCK_REALTIME` at the moment it is written.
+    Realtime,
+    /// Keep the timestamps recorded in the input.
+    Original,
+}
+
+/// Radix of numbers in the input that have no `0x` or `0d` prefix.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Radix {
+    /// Hexadecimal, as written by getevent.
+    #[default]
+    Hex,
+    Decimal,
+}
+
+impl str::FromStr for Radix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(Radix::Hex),
+            "decimal" => Ok(Radix::Decimal),
+            _ => Err(format!(
+                "unknown radix {:?}, expected \"hex\" or \"decimal\"",
+                s
+            )),
         }
-    } else {
-        return Box::new(iter::empty());
-    };
-    let options = match get_options(&line) {
-        Ok(options) => options,
-        Err(error) => return Box::new(iter::once(Err(error))),
-    };
-    Box::new(lines.map(move |result| match result {
-        Ok(line) => parse_event(&line, &options),
-        Err(error) => Err(Error::IO(error)),
-    }))
-}
-
-pub fn send_events_from_reader(reader: &mut impl BufRead, device: Option<&str>) {
-    fn preprocess_result<'a>(
-        device: &Option<&str>,
-        opened_devices: &'a mut HashMap<String, File>,
-        result: Result<(Option<String>, InputEvent), Error>,
-    ) -> Option<(&'a mut File, InputEvent)> {
-        let (device, event) = match result {
-            Ok((option, event)) => (
-                if let Some(device) = option {
-                    device
-                } else {
-                    device.unwrap().to_string()
+    }
+}
+
+/// What to do when the time between two packets is negative or larger than
+/// [`SendOptions::max_gap`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Discontinuity {
+    /// Fail with the line number of the packet.
+    Error,
+    /// Write the packet without delay and schedule later packets relative to it.
+    Rebase,
+    /// Write the packet without delay if time goes backwards, or after
+    /// [`SendOptions::max_gap`] if it jumps forward, and schedule later packets relative to it.
+    #[default]
+    Clamp,
+}
+
+#[derive(Debug, Default)]
+pub struct SendOptions {
+    pub timestamp: Timestamp,
+    pub discontinuity: Discontinuity,
+    /// Forward gaps between packets larger than this are handled as discontinuities.
+    pub max_gap: Option<Duration>,
+    /// Shift the timestamps of each recording so that they all start at the same time, instead
+    /// of merging them by their absolute timestamps.
+    pub align_start: bool,
+    pub filter: Filter,
+    /// Convert multitouch events to this protocol.
+    pub protocol: Option<MtProtocol>,
+    pub jitter: Jitter,
+    /// Time between the packets of input without timestamps.
+    pub interval: Duration,
+    /// Radix of numbers without a prefix, which `# radix` lines in the input override.
+    pub radix: Radix,
+}
+
+/// Input in the format of getevent, with the device used for lines without a device field.
+pub struct Recording<'a> {
+    pub reader: &'a mut dyn BufRead,
+    /// Path of the recording, which errors in it are reported with.
+    pub path: Option<&'a str>,
+    pub device: Option<&'a str>,
+}
+
+/// Events of one device up to and including the `SYN_REPORT` event that terminates them.
+#[derive(Debug)]
+struct Packet {
+    device: String,
+    events: Vec<InputEvent>,
+    /// Line number of the last event.
+    line: usize,
+}
+
+impl Packet {
+    /// Synthesized events of one device at `time`, followed by a `SYN_REPORT` event. Events are
+    /// given as type, code and value.
+    fn new(
+        device: &str,
+        time: Duration,
+        events: impl IntoIterator<Item = (i32, i32, i32)>,
+    ) -> Self {
+        let time = TimeVal::from_duration(time);
+        let syn_report = (input_event_codes::EV_SYN, input_event_codes::SYN_REPORT, 0);
+        Packet {
+            device: device.to_string(),
+            events: events
+                .into_iter()
+                .chain(iter::once(syn_report))
+                .map(|(r#type, code, value)| InputEvent {
+                    time: time.clone(),
+                    r#type: r#type as u16,
+                    code: code as u16,
+                    value,
+                })
+                .collect(),
+            line: 0,
+        }
+    }
+
+    fn time(&self) -> Duration {
+        self.events.last().unwrap().time.to_duration()
+    }
+}
+
+/// Groups events into packets per device.
+struct Packets<I> {
+    iterator: I,
+    device: Option<String>,
+    pending: Vec<Packet>,
+}
+
+impl<I: Iterator<Item = (usize, ParseResult)>> Iterator for Packets<I> {
+    type Item = Result<Packet, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (line, result) in self.iterator.by_ref() {
+            let (device, event) = match result {
+                Ok(result) => result,
+                Err(error) => return Some(Err(Error::Line(line, Box::new(error)))),
+            };
+            let device = if let Some(device) = device.or_else(|| self.device.clone()) {
+                device
+            } else {
+                return Some(Err(Error::Line(
+                    line,
+                    Box::new(Error::Format(
+                        "missing device field and no default device".to_string(),
+                    )),
+                )));
+            };
+            let index = if let Some(index) = self.pending.iter().position(|p| p.device == device) {
+                index
+            } else {
+                self.pending.push(Packet {
+                    device,
+                    events: vec![],
+                    line,
+                });
+                self.pending.len() - 1
+            };
+            let is_syn_report = is_syn_report(&event);
+            self.pending[index].events.push(event);
+            self.pending[index].line = line;
+            if is_syn_report {
+                return Some(Ok(self.pending.remove(index)));
+            }
+        }
+        // Events after the last `SYN_REPORT` event of a device form an unterminated packet.
+        if self.pending.is_empty() {
+            None
+        } else {
+            Some(Ok(self.pending.remove(0)))
+        }
+    }
+}
+
+fn packets<I: Iterator<Item = (usize, ParseResult)>>(
+    iterator: I,
+    device: Option<&str>,
+) -> Packets<I> {
+    Packets {
+        iterator,
+        device: device.map(str::to_string),
+        pending: vec![],
+    }
+}
+
+/// Computes when each packet is due, relative to the first packet.
+struct Schedule {
+    discontinuity: Discontinuity,
+    max_gap: Option<Duration>,
+    /// Time and offset of the previous packet.
+    previous: Option<(Duration, Duration)>,
+}
+
+impl Schedule {
+    fn new(options: &SendOptions) -> Self {
+        Schedule {
+            discontinuity: options.discontinuity,
+            max_gap: options.max_gap,
+            previous: None,
+        }
+    }
+
+    fn offset(&mut self, packet: &Packet) -> Result<Duration, Error> {
+        let time = packet.time();
+        let offset = if let Some((previous_time, previous_offset)) = self.previous {
+            let gap = time.checked_sub(previous_time);
+            match (gap, self.max_gap) {
+                (Some(gap), Some(max_gap)) if gap > max_gap => match self.discontinuity {
+                    Discontinuity::Error => {
+                        return Err(Error::Line(
+                            packet.line,
+                            Box::new(Error::Time(format!("timestamp jumps forward by {:?}", gap))),
+                        ))
+                    }
+                    Discontinuity::Rebase => previous_offset,
+                    Discontinuity::Clamp => previous_offset + max_gap,
                 },
-                event,
-            ),
-            Err(error) => panic!("{:?}", error),
+                (Some(gap), _) => previous_offset + gap,
+                (None, _) => match self.discontinuity {
+                    Discontinuity::Error => {
+                        return Err(Error::Line(
+                            packet.line,
+                            Box::new(Error::Time(format!(
+                                "timestamp goes backwards by {:?}",
+                                previous_time - time
+                            ))),
+                        ))
+                    }
+                    Discontinuity::Rebase | Discontinuity::Clamp => previous_offset,
+                },
+            }
+        } else {
+            Duration::ZERO
         };
-        let event = InputEvent {
-            time: TimeVal { sec: 0, usec: 0 },
-            ..event
+        self.previous = Some((time, offset));
+        Ok(offset)
+    }
+}
+
+/// Packets of all recordings, merged in the order in which they are due, filtered, converted
+/// to [`SendOptions::protocol`] and perturbed by [`SendOptions::jitter`].
+fn merged_packets<'a>(
+    recordings: Vec<Recording<'a>>,
+    options: &SendOptions,
+) -> impl Iterator<Item = Result<Packet, Error>> + 'a {
+    let filter = options.filter.clone();
+    let mut converter = options.protocol.map(protocol::Converter::new);
+    let mut perturber = jitter::Perturber::new(&options.jitter);
+    Merge::new(
+        recordings
+            .into_iter()
+            .map(|recording| {
+                let path = recording.path;
+                packets(
+                    parse_paced(recording.reader, options.interval, options.radix),
+                    recording.device,
+                )
+                .map(move |result| match path {
+                    Some(path) => {
+                        result.map_err(|error| Error::Include(path.to_string(), Box::new(error)))
+                    }
+                    None => result,
+                })
+            })
+            .collect(),
+        options.align_start,
+    )
+    .filter_map(move |result| match result {
+        Ok(packet) => filter.apply(packet).map(Ok),
+        Err(error) => Some(Err(error)),
+    })
+    .map(move |result| {
+        result.map(|packet| match &mut converter {
+            Some(converter) => converter.apply(packet),
+            None => packet,
+        })
+    })
+    .filter_map(move |result| match result {
+        Ok(packet) => perturber.apply(packet).map(Ok),
+        Err(error) => Some(Err(error)),
+    })
+}
+
+fn is_syn_report(event: &InputEvent) -> bool {
+    i32::from(event.r#type) == input_event_codes::EV_SYN
+        && i32::from(event.code) == input_event_codes::SYN_REPORT
+}
+
+/// Parses a duration such as `300ms`, `1.5s` or `250us`. A number without a unit is in seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let (number, unit) = s.split_at(
+        s.find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(s.len()),
+    );
+    let scale = match unit {
+        "" | "s" => 1.0,
+        "ms" => 1e-3,
+        "us" => 1e-6,
+        "m" => 60.0,
+        _ => return Err(format!("unknown unit {:?} in duration {:?}", unit, s)),
+    };
+    match number.parse::<f64>() {
+        Ok(number) => Duration::try_from_secs_f64(number * scale)
+            .map_err(|error| format!("{} in duration {:?}", error, s)),
+        Err(error) => Err(format!("{} in duration {:?}", error, s)),
+    }
+}
+
+/// Writes all events of a packet with a single `write()`.
+fn write_packet(
+    device: &mut impl Write,
+    events: Vec<InputEvent>,
+    timestamp: Timestamp,
+) -> Result<(), io::Error> {
+    let now = match timestamp {
+        Timestamp::Zero | Timestamp::Original => TimeVal { sec: 0, usec: 0 },
+        Timestamp::Monotonic => TimeVal::now(libc::CLOCK_MONOTONIC),
+        Timestamp::Realtime => TimeVal::now(libc::CLOCK_REALTIME),
+    };
+    let mut buffer = Vec::with_capacity(events.len() * mem::size_of::<InputEvent>());
+    for event in events {
+        let time = if timestamp == Timestamp::Original {
+            event.time
+        } else {
+            now.clone()
         };
-        let device = opened_devices
-            .entry(device.clone())
-            .or_insert_with(|| File::options().write(true).open(&device).unwrap());
-        if !(i32::try_from(event.r#type).unwrap() == input_event_codes::EV_SYN
-            && i32::try_from(event.code).unwrap() == input_event_codes::SYN_REPORT)
-        {
-            write_event(device, &event).unwrap();
-            return None;
+        write_event(&mut buffer, &InputEvent { time, ..event })?;
+    }
+    device.write_all(&buffer)
+}
+
+pub fn send_events_from_reader(
+    reader: &mut impl BufRead,
+    device: Option<&str>,
+    options: &SendOptions,
+) -> Result<TimingReport, Error> {
+    send_events_from_recordings(
+        vec![Recording {
+            reader,
+            path: None,
+            device,
+        }],
+        options,
+    )
+}
+
+pub fn send_events_from_recordings(
+    recordings: Vec<Recording>,
+    options: &SendOptions,
+) -> Result<TimingReport, Error> {
+    send_packets(merged_packets(recordings, options), options)
+}
+
+/// Writes the packets of a gesture to the device of the generator.
+pub fn send_gesture(
+    generator: &mut GestureGenerator,
+    gesture: &Gesture,
+    options: &SendOptions,
+) -> Result<TimingReport, Error> {
+    send_packets(generator.generate(gesture)?.into_iter().map(Ok), options)
+}
+
+/// Types text on the device of the typist.
+pub fn send_text(
+    typist: &Typist,
+    text: &str,
+    options: &SendOptions,
+) -> Result<TimingReport, Error> {
+    send_packets(typist.generate(text)?.into_iter().map(Ok), options)
+}
+
+/// Writes the events of a compiled script.
+pub fn send_script(script: Script, options: &SendOptions) -> Result<TimingReport, Error> {
+    send_packets(script.finish().into_iter().map(Ok), options)
+}
+
+fn send_packets(
+    packets: impl Iterator<Item = Result<Packet, Error>>,
+    options: &SendOptions,
+) -> Result<TimingReport, Error> {
+    let mut opened_devices: HashMap<String, File> = HashMap::new();
+    let mut held_keys = HeldKeys::default();
+    let result = write_packets(packets, options, &mut opened_devices, &mut held_keys);
+    // Keys that are still down when the input ends or fails would keep repeating.
+    let mut release = Ok(());
+    for packet in held_keys.release() {
+        if let Some(device) = opened_devices.get_mut(&packet.device) {
+            release = release.and(write_packet(device, packet.events, options.timestamp));
         }
-        Some((device, event))
     }
+    let report = result?;
+    release?;
+    Ok(report)
+}
 
-    let mut opened_devices = HashMap::new();
+fn write_packets(
+    packets: impl Iterator<Item = Result<Packet, Error>>,
+    options: &SendOptions,
+    opened_devices: &mut HashMap<String, File>,
+    held_keys: &mut HeldKeys,
+) -> Result<TimingReport, Error> {
+    let mut schedule = Schedule::new(options);
+    let mut start = None;
+    let mut report = TimingReport::default();
 
-    let mut iterator = parse_all(reader);
-    if let Some((base_system_time, base_event_time)) = loop {
-        if let Some(result) = iterator.next() {
-            if let Some((device, event)) = preprocess_result(&device, &mut opened_devices, result) {
-                let base_event_time = event.time.to_duration();
-                let base_system_time = SystemTime::now();
-                write_event(device, &event).unwrap();
-                break Some((base_system_time, base_event_time));
-            }
-            continue;
+    for result in packets {
+        let packet = match held_keys.apply(result?) {
+            Some(packet) => packet,
+            None => continue,
+        };
+        let device = match opened_devices.entry(packet.device.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(File::options().write(true).open(&packet.device)?),
+        };
+        let offset = schedule.offset(&packet)?;
+        let start = *start.get_or_insert_with(Instant::now);
+        thread::sleep(offset.saturating_sub(start.elapsed()));
+        write_packet(device, packet.events, options.timestamp)?;
+        report.record(start.elapsed().saturating_sub(offset));
+    }
+    Ok(report)
+}
+
+pub fn dry_run_from_reader(
+    reader: &mut impl BufRead,
+    device: Option<&str>,
+    options: &SendOptions,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    dry_run_from_recordings(
+        vec![Recording {
+            reader,
+            path: None,
+            device,
+        }],
+        options,
+        output,
+    )
+}
+
+/// Writes an event in the format of `getevent -l -t`.
+fn write_event_line(
+    output: &mut impl Write,
+    time: Duration,
+    device: &str,
+    event: &InputEvent,
+) -> Result<(), io::Error> {
+    writeln!(
+        output,
+        "[{:>8}.{:06}] {}: {}",
+        time.as_secs(),
+        time.subsec_micros(),
+        device,
+        event
+    )
+}
+
+/// Writes the events of all recordings, merged and filtered, in the format of `getevent -l -t`.
+pub fn convert_recordings(
+    recordings: Vec<Recording>,
+    options: &SendOptions,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    convert_packets(merged_packets(recordings, options), output)
+}
+
+/// Writes the events of a compiled script in the format of `getevent -l -t`, with timestamps
+/// starting at zero, which `replay` accepts.
+pub fn convert_script(script: Script, output: &mut impl Write) -> Result<(), Error> {
+    convert_packets(script.finish().into_iter().map(Ok), output)
+}
+
+/// Writes a script of the actions in all recordings, merged and filtered, that
+/// [`Script`] compiles back into similar events.
+pub fn decompile_recordings(
+    recordings: Vec<Recording>,
+    options: &SendOptions,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    decompile::decompile(merged_packets(recordings, options), output)
+}
+
+fn convert_packets(
+    packets: impl Iterator<Item = Result<Packet, Error>>,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    for result in packets {
+        let packet = result?;
+        for event in &packet.events {
+            write_event_line(output, event.time.to_duration(), &packet.device, event)?;
         }
-        break None;
-    } {
-        for result in iterator {
-            if let Some((device, event)) = preprocess_result(&device, &mut opened_devices, result) {
-                let current_event_time = event.time.to_duration();
-                let eta = current_event_time - base_event_time;
-                let now = base_system_time.elapsed().unwrap();
-                let delay = eta.saturating_sub(now);
-                thread::sleep(delay);
-                write_event(device, &event).unwrap();
-            }
+    }
+    Ok(())
+}
+
+/// Computes statistics of all recordings, merged and filtered. Gaps between packets longer
+/// than `gap_threshold` are listed.
+pub fn info_from_recordings(
+    recordings: Vec<Recording>,
+    options: &SendOptions,
+    gap_threshold: Duration,
+) -> Result<RecordingInfo, Error> {
+    let mut info = RecordingInfo::new(gap_threshold);
+    for result in merged_packets(recordings, options) {
+        info.record(&result?);
+    }
+    Ok(info)
+}
+
+/// Renders the paths of the touch contacts in all recordings, merged and filtered, as an SVG
+/// image that is `width` pixels wide. The contacts must all be on one device, whose axis ranges
+/// are taken from `device_info` if it is there.
+pub fn render_svg(
+    recordings: Vec<Recording>,
+    options: &SendOptions,
+    device_info: &BTreeMap<String, DeviceInfo>,
+    width: u32,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    svg::render(
+        merged_packets(recordings, options),
+        device_info,
+        width,
+        output,
+    )
+}
+
+/// Finds semantic problems in a recording, checking events of the devices in `device_info`
+/// against their capabilities. Numbers without a prefix are read in `radix`. Fails only if the
+/// input cannot be read.
+pub fn validate_recording(
+    reader: &mut impl BufRead,
+    device: Option<&str>,
+    radix: Radix,
+    device_info: &BTreeMap<String, DeviceInfo>,
+) -> Result<Vec<Problem>, Error> {
+    validate::validate(
+        parse_paced(reader, Duration::ZERO, radix),
+        device,
+        device_info,
+    )
+}
+
+/// Prints the events that would be written and when, without opening any device.
+pub fn dry_run_from_recordings(
+    recordings: Vec<Recording>,
+    options: &SendOptions,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    dry_run_packets(merged_packets(recordings, options), options, output)
+}
+
+/// Prints the events of a gesture like [`dry_run_from_recordings`].
+pub fn dry_run_gesture(
+    generator: &mut GestureGenerator,
+    gesture: &Gesture,
+    options: &SendOptions,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    dry_run_packets(
+        generator.generate(gesture)?.into_iter().map(Ok),
+        options,
+        output,
+    )
+}
+
+/// Prints the events that type text like [`dry_run_from_recordings`].
+pub fn dry_run_text(
+    typist: &Typist,
+    text: &str,
+    options: &SendOptions,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    dry_run_packets(typist.generate(text)?.into_iter().map(Ok), options, output)
+}
+
+/// Prints the events of a compiled script like [`dry_run_from_recordings`].
+pub fn dry_run_script(
+    script: Script,
+    options: &SendOptions,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    dry_run_packets(script.finish().into_iter().map(Ok), options, output)
+}
+
+fn dry_run_packets(
+    packets: impl Iterator<Item = Result<Packet, Error>>,
+    options: &SendOptions,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    let mut counts: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    let mut schedule = Schedule::new(options);
+    let mut duration = Duration::ZERO;
+    let mut held_keys = HeldKeys::default();
+
+    let mut print = |offset, packet: Packet| -> Result<(), Error> {
+        for event in &packet.events {
+            write_event_line(output, offset, &packet.device, event)?;
         }
+        let (packets, events) = counts.entry(packet.device).or_default();
+        *packets += 1;
+        *events += packet.events.len();
+        Ok(())
+    };
+
+    for result in packets {
+        let packet = match held_keys.apply(result?) {
+            Some(packet) => packet,
+            None => continue,
+        };
+        let offset = schedule.offset(&packet)?;
+        print(offset, packet)?;
+        duration = offset;
+    }
+    // The keys that are still down at the end are released right after the last packet.
+    for packet in held_keys.release() {
+        print(duration, packet)?;
+    }
+
+    writeln!(
+        output,
+        "duration: {}.{:06}s",
+        duration.as_secs(),
+        duration.subsec_micros()
+    )?;
+    for (device, (packets, events)) in counts {
+        writeln!(output, "{}: {} packets, {} events", device, packets, events)?;
     }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        linux::input_event_codes, parse_event, write_event, Error, InputEvent, Options, TimeVal,
+        convert_recordings, dry_run_from_reader, linux::input_event_codes, packets, parse_all,
+        parse_duration, parse_event, parse_paced, write_event, write_packet, Discontinuity, Error,
+        InputEvent, Options, Radix, Recording, Schedule, SendOptions, TimeVal, Timestamp,
     };
+    use std::io::{self, Write};
     use std::time::Duration;
 
     #[test]
@@ -351,7 +1102,7 @@ mod tests {
                 run!($pattern, $line, $options, {});
             };
             ($pattern:pat, $line:expr, $options:expr, $statement:expr) => {
-                if let $pattern = parse_event($line, &$options) {
+                if let $pattern = parse_event($line, &$options, Radix::Hex) {
                     $statement;
                 } else {
                     assert!(false);
@@ -422,6 +1173,30 @@ mod tests {
                 print_device: false,
             }
         );
+        run!(
+            Err(Error::Time(..)),
+            "[ -1.000000] EV_SYN SYN_REPORT 0",
+            Options {
+                get_time: true,
+                print_device: false,
+            }
+        );
+        run!(
+            Err(Error::Time(..)),
+            "[0.1000000] EV_SYN SYN_REPORT 0",
+            Options {
+                get_time: true,
+                print_device: false,
+            }
+        );
+        run!(
+            Err(Error::ParseInt(..)),
+            "[0.-1] EV_SYN SYN_REPORT 0",
+            Options {
+                get_time: true,
+                print_device: false,
+            }
+        );
         run!(
             Err(Error::Format(..)),
             "[0.0] ",
@@ -439,7 +1214,7 @@ mod tests {
             }
         );
         run!(
-            Err(Error::ParseInt(..)),
+            Err(Error::Parse(..)),
             "-1",
             Options {
                 get_time: false,
@@ -455,7 +1230,7 @@ mod tests {
             }
         );
         run!(
-            Err(Error::ParseInt(..)),
+            Err(Error::Parse(..)),
             "0 -1",
             Options {
                 get_time: false,
@@ -463,7 +1238,7 @@ mod tests {
             }
         );
         run!(
-            Err(Error::ParseInt(..)),
+            Err(Error::Parse(..)),
             "0 -1",
             Options {
                 get_time: false,
@@ -479,8 +1254,8 @@ mod tests {
             }
         );
         run!(
-            Err(Error::ParseInt(..)),
-            "0 0 -1",
+            Err(Error::Parse(..)),
+            "0 0 --1",
             Options {
                 get_time: false,
                 print_device: false,
@@ -632,4 +1407,364 @@ mod tests {
             value: 5,
         });
     }
+
+    #[test]
+    fn test_write_packet() {
+        struct CountingWriter {
+            writes: usize,
+            data: Vec<u8>,
+        }
+
+        impl Write for CountingWriter {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.writes += 1;
+                self.data.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let events = || {
+            vec![
+                InputEvent {
+                    time: TimeVal { sec: 1, usec: 2 },
+                    r#type: input_event_codes::EV_ABS.try_into().unwrap(),
+                    code: input_event_codes::ABS_MT_POSITION_X.try_into().unwrap(),
+                    value: 1,
+                },
+                InputEvent {
+                    time: TimeVal { sec: 1, usec: 2 },
+                    r#type: input_event_codes::EV_ABS.try_into().unwrap(),
+                    code: input_event_codes::ABS_MT_POSITION_Y.try_into().unwrap(),
+                    value: 2,
+                },
+                InputEvent {
+                    time: TimeVal { sec: 1, usec: 2 },
+                    r#type: input_event_codes::EV_SYN.try_into().unwrap(),
+                    code: input_event_codes::SYN_REPORT.try_into().unwrap(),
+                    value: 0,
+                },
+            ]
+        };
+        let mut device = CountingWriter {
+            writes: 0,
+            data: vec![],
+        };
+
+        write_packet(&mut device, events(), Timestamp::Original).unwrap();
+        assert_eq!(device.writes, 1);
+        assert_eq!(
+            device.data,
+            events()
+                .iter()
+                .flat_map(|event| event.to_ne_bytes())
+                .collect::<Vec<_>>()
+        );
+
+        device.data.clear();
+        write_packet(&mut device, events(), Timestamp::Zero).unwrap();
+        assert_eq!(device.writes, 2);
+        assert_eq!(
+            device.data,
+            events()
+                .into_iter()
+                .flat_map(|event| InputEvent {
+                    time: TimeVal { sec: 0, usec: 0 },
+                    ..event
+                }
+                .to_ne_bytes())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_packets() {
+        let input = "\
+[       0.000000] /dev/input/event1: EV_ABS ABS_MT_POSITION_X 00000001
+[       0.000000] /dev/input/event2: EV_KEY KEY_POWER DOWN
+[       0.000000] /dev/input/event1: EV_SYN SYN_REPORT 00000000
+[       0.100000] /dev/input/event1: EV_ABS ABS_MT_POSITION_Y 00000003
+[       0.200000] /dev/input/event2: EV_SYN SYN_REPORT 00000000
+[       0.300000] /dev/input/event1: EV_ABS ABS_MT_POSITION_Y 00000004
+";
+        let mut reader = input.as_bytes();
+        let result = packets(parse_all(&mut reader), None)
+            .map(|result| {
+                let packet = result.unwrap();
+                (packet.time(), packet.device, packet.events.len())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            result,
+            [
+                (Duration::ZERO, "/dev/input/event1".to_string(), 2),
+                (
+                    Duration::from_millis(200),
+                    "/dev/input/event2".to_string(),
+                    2
+                ),
+                (
+                    Duration::from_millis(300),
+                    "/dev/input/event1".to_string(),
+                    2
+                ),
+            ]
+        );
+
+        let mut reader = "0000 0000 00000000\n".as_bytes();
+        assert!(matches!(
+            packets(parse_all(&mut reader), None).next(),
+            Some(Err(Error::Line(1, error))) if matches!(*error, Error::Format(..))
+        ));
+    }
+
+    #[test]
+    fn test_comments() {
+        let input = "\
+# A tap, captured with getevent -l -t.
+
+[       1.000000] /dev/input/event2: EV_KEY BTN_TOUCH DOWN   # touch
+[       1.000000] /dev/input/event2: EV_SYN SYN_REPORT 00000000
+  # lift
+[       1.100000] /dev/input/event2: EV_KEY BTN_TOUCH UP\t# lift
+[       1.100000] /dev/input/event2: EV_SYN SYN_REPORT 00000000#
+[       1.200000] /dev/input/event2: EV_SYN SYN_REPORT #00000000
+";
+        let result = parse_all(&mut input.as_bytes())
+            .map(|(line, result)| (line, result.map(|(_, event)| event.value)))
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            result[..],
+            [
+                (3, Ok(1)),
+                (4, Ok(0)),
+                (6, Ok(0)),
+                (7, Err(Error::Parse(..))),
+                (8, Err(Error::Format(..))),
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_pacing() {
+        let input = "\
+/dev/input/event0: EV_KEY KEY_A DOWN
+/dev/input/event0: EV_SYN SYN_REPORT 00000000
+# sleep 500ms
+/dev/input/event0: EV_KEY KEY_A UP
+/dev/input/event0: EV_SYN SYN_REPORT 00000000
+/dev/input/event1: EV_SYN SYN_REPORT 00000000
+";
+        let times = |interval| {
+            packets(
+                parse_paced(&mut input.as_bytes(), interval, Radix::Hex),
+                None,
+            )
+            .map(|result| result.unwrap().time().as_millis())
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(times(Duration::ZERO), [0, 500, 500]);
+        assert_eq!(times(Duration::from_millis(20)), [0, 520, 540]);
+
+        let input = "\
+[       1.000000] /dev/input/event0: EV_SYN SYN_REPORT 00000000
+# sleep 1s
+";
+        let mut reader = input.as_bytes();
+        assert_eq!(
+            packets(
+                parse_paced(&mut reader, Duration::from_millis(20), Radix::Hex),
+                None
+            )
+            .map(|result| result.map_err(|error| error.to_string()))
+            .last()
+            .unwrap()
+            .unwrap_err(),
+            "line 2: `# sleep` is only allowed in input without timestamps"
+        );
+        // Whether `# sleep` is allowed before the first event depends on the first event.
+        let mut reader =
+            "# sleep 1s\n[       1.000000] /dev/input/event0: EV_SYN SYN_REPORT 0\n".as_bytes();
+        assert_eq!(
+            packets(parse_all(&mut reader), None)
+                .next()
+                .unwrap()
+                .unwrap_err()
+                .to_string(),
+            "line 1: `# sleep` is only allowed in input without timestamps"
+        );
+        let mut reader = "# sleep 1h\n".as_bytes();
+        assert_eq!(
+            packets(parse_all(&mut reader), None)
+                .next()
+                .unwrap()
+                .unwrap_err()
+                .to_string(),
+            "line 1: unknown unit \"h\" in duration \"1h\""
+        );
+    }
+
+    #[test]
+    fn test_radix() {
+        let input = "\
+/dev/input/event2: EV_ABS ABS_MT_POSITION_X 540
+# radix decimal # for the values below
+/dev/input/event2: EV_ABS ABS_MT_POSITION_X 540
+/dev/input/event2: EV_REL REL_WHEEL -1
+/dev/input/event2: EV_ABS ABS_MT_POSITION_X 0x540
+# radix hex
+/dev/input/event2: EV_ABS ABS_MT_POSITION_X 0d540
+/dev/input/event2: EV_ABS ABS_MT_TRACKING_ID ffffffff
+";
+        let values = |radix| {
+            parse_paced(&mut input.as_bytes(), Duration::ZERO, radix)
+                .map(|(_, result)| result.unwrap().1.value)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values(Radix::Hex), [0x540, 540, -1, 0x540, 540, -1]);
+        assert_eq!(values(Radix::Decimal), [540, 540, -1, 0x540, 540, -1]);
+
+        let mut reader = "# radix octal\n".as_bytes();
+        assert_eq!(
+            parse_all(&mut reader)
+                .next()
+                .unwrap()
+                .1
+                .unwrap_err()
+                .to_string(),
+            "unknown radix \"octal\", expected \"hex\" or \"decimal\""
+        );
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let input = "\
+[    1000.000000] EV_KEY KEY_POWER DOWN
+[    1000.000000] EV_SYN SYN_REPORT 00000000
+[    1000.250000] EV_KEY KEY_POWER UP
+[    1000.250000] EV_SYN SYN_REPORT 00000000
+";
+        let mut output = vec![];
+        dry_run_from_reader(
+            &mut input.as_bytes(),
+            Some("/dev/input/event0"),
+            &SendOptions::default(),
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\
+[       0.000000] /dev/input/event0: EV_KEY       KEY_POWER            DOWN
+[       0.000000] /dev/input/event0: EV_SYN       SYN_REPORT           00000000
+[       0.250000] /dev/input/event0: EV_KEY       KEY_POWER            UP
+[       0.250000] /dev/input/event0: EV_SYN       SYN_REPORT           00000000
+duration: 0.250000s
+/dev/input/event0: 2 packets, 4 events
+"
+        );
+    }
+
+    #[test]
+    fn recording_errors() {
+        let mut touch = "\
+[       1.000000] EV_SYN SYN_REPORT 00000000
+"
+        .as_bytes();
+        let mut keys = "\
+[       0.500000] EV_KEY KEY_POWER DOWN
+[       2.000000] EV_KEY KEY_POWR UP
+"
+        .as_bytes();
+        let recordings = vec![
+            Recording {
+                reader: &mut touch,
+                path: Some("touch"),
+                device: Some("/dev/input/event2"),
+            },
+            Recording {
+                reader: &mut keys,
+                path: Some("keys"),
+                device: Some("/dev/input/event0"),
+            },
+        ];
+        let error = convert_recordings(recordings, &SendOptions::default(), &mut io::sink())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.starts_with("keys: line 2: unknown EV_KEY code \"KEY_POWR\""),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("300ms"), Ok(Duration::from_millis(300)));
+        assert_eq!(parse_duration("250us"), Ok(Duration::from_micros(250)));
+        assert_eq!(parse_duration("1m"), Ok(Duration::from_secs(60)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("ms").is_err());
+        assert!(parse_duration("-1s").is_err());
+        assert!(parse_duration("1h").is_err());
+        assert!(parse_duration("100000000000000000000000m").is_err());
+    }
+
+    #[test]
+    fn test_schedule() {
+        let input = "\
+[       5.000000] EV_SYN SYN_REPORT 00000000
+[       5.500000] EV_SYN SYN_REPORT 00000000
+[       1.000000] EV_SYN SYN_REPORT 00000000
+[       1.100000] EV_SYN SYN_REPORT 00000000
+[     101.100000] EV_SYN SYN_REPORT 00000000
+[     101.200000] EV_SYN SYN_REPORT 00000000
+";
+        let offsets = |discontinuity, max_gap| {
+            let mut schedule = Schedule::new(&SendOptions {
+                discontinuity,
+                max_gap,
+                ..SendOptions::default()
+            });
+            packets(parse_all(&mut input.as_bytes()), Some(""))
+                .map(|packet| schedule.offset(&packet.unwrap()))
+                .collect::<Result<Vec<_>, _>>()
+                .map(|offsets| {
+                    offsets
+                        .into_iter()
+                        .map(|offset| offset.as_millis())
+                        .collect::<Vec<_>>()
+                })
+        };
+
+        assert_eq!(
+            offsets(Discontinuity::Clamp, None).unwrap(),
+            [0, 500, 500, 600, 100600, 100700]
+        );
+        assert_eq!(
+            offsets(Discontinuity::Clamp, Some(Duration::from_secs(1))).unwrap(),
+            [0, 500, 500, 600, 1600, 1700]
+        );
+        assert_eq!(
+            offsets(Discontinuity::Rebase, Some(Duration::from_secs(1))).unwrap(),
+            [0, 500, 500, 600, 600, 700]
+        );
+        assert!(matches!(
+            offsets(Discontinuity::Error, None),
+            Err(Error::Line(3, error)) if matches!(*error, Error::Time(..))
+        ));
+        assert!(matches!(
+            offsets(Discontinuity::Error, Some(Duration::from_secs(1))),
+            Err(Error::Line(3, ..))
+        ));
+        assert!(matches!(
+            offsets(Discontinuity::Error, Some(Duration::from_secs(5))),
+            Err(Error::Line(3, ..))
+        ));
+    }
 }
@@ -1,5 +1,49 @@
-pub const EV_VERSION: i32 = 0x010001;
+#![allow(non_camel_case_types, non_snake_case)]
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct input_absinfo {
+    pub value: i32,
+    pub minimum: i32,
+    pub maximum: i32,
+    pub fuzz: i32,
+    pub flat: i32,
+    pub resolution: i32,
+}
+
+const _IOC_NRSHIFT: u32 = 0;
+const _IOC_TYPESHIFT: u32 = 8;
+const _IOC_SIZESHIFT: u32 = 16;
+const _IOC_DIRSHIFT: u32 = 30;
+const _IOC_READ: u32 = 2;
+
+const fn _IOC(dir: u32, r#type: u32, nr: u32, size: u32) -> u32 {
+    (dir << _IOC_DIRSHIFT)
+        | (r#type << _IOC_TYPESHIFT)
+        | (nr << _IOC_NRSHIFT)
+        | (size << _IOC_SIZESHIFT)
+}
+
+pub const fn EVIOCGPROP(len: u32) -> u32 {
+    _IOC(_IOC_READ, b'E' as u32, 0x09, len)
+}
 
+pub const fn EVIOCGBIT(ev: u32, len: u32) -> u32 {
+    _IOC(_IOC_READ, b'E' as u32, 0x20 + ev, len)
+}
+
+pub const fn EVIOCGABS(abs: u32) -> u32 {
+    _IOC(
+        _IOC_READ,
+        b'E' as u32,
+        0x40 + abs,
+        std::mem::size_of::<input_absinfo>() as u32,
+    )
+}
+
+// Generated by `cargo xtask codegen` from input.h, do not edit below.
+
+pub const EV_VERSION: i32 = 0x010001;
 pub const MT_TOOL_FINGER: i32 = 0x00;
 pub const MT_TOOL_PEN: i32 = 0x01;
 pub const MT_TOOL_PALM: i32 = 0x02;
@@ -8,7 +52,6 @@ pub const MT_TOOL_MAX: i32 = 0x0f;
 pub const FF_STATUS_STOPPED: i32 = 0x00;
 pub const FF_STATUS_PLAYING: i32 = 0x01;
 pub const FF_STATUS_MAX: i32 = 0x01;
-
 pub const FF_RUMBLE: i32 = 0x50;
 pub const FF_PERIODIC: i32 = 0x51;
 pub const FF_CONSTANT: i32 = 0x52;
@@ -31,3 +74,4 @@ pub const FF_GAIN: i32 = 0x60;
 pub const FF_AUTOCENTER: i32 = 0x61;
 pub const FF_MAX_EFFECTS: i32 = FF_GAIN;
 pub const FF_MAX: i32 = 0x7f;
+pub const FF_CNT: i32 = FF_MAX + 1;
@@ -1,3 +1,5 @@
+// Generated by `cargo xtask codegen`, do not edit.
+
 pub const INPUT_PROP_POINTER: i32 = 0x00;
 pub const INPUT_PROP_DIRECT: i32 = 0x01;
 pub const INPUT_PROP_BUTTONPAD: i32 = 0x02;
@@ -447,6 +449,7 @@ pub const KEY_IMAGES: i32 = 0x1ba;
 pub const KEY_NOTIFICATION_CENTER: i32 = 0x1bc;
 pub const KEY_PICKUP_PHONE: i32 = 0x1bd;
 pub const KEY_HANGUP_PHONE: i32 = 0x1be;
+pub const KEY_LINK_PHONE: i32 = 0x1bf;
 pub const KEY_DEL_EOL: i32 = 0x1c0;
 pub const KEY_DEL_EOS: i32 = 0x1c1;
 pub const KEY_INS_LINE: i32 = 0x1c2;
@@ -520,6 +523,7 @@ pub const BTN_DPAD_LEFT: i32 = 0x222;
 pub const BTN_DPAD_RIGHT: i32 = 0x223;
 pub const KEY_ALS_TOGGLE: i32 = 0x230;
 pub const KEY_ROTATE_LOCK_TOGGLE: i32 = 0x231;
+pub const KEY_REFRESH_RATE_TOGGLE: i32 = 0x232;
 pub const KEY_BUTTONCONFIG: i32 = 0x240;
 pub const KEY_TASKMANAGER: i32 = 0x241;
 pub const KEY_JOURNAL: i32 = 0x242;
@@ -700,6 +704,7 @@ pub const ABS_TILT_X: i32 = 0x1a;
 pub const ABS_TILT_Y: i32 = 0x1b;
 pub const ABS_TOOL_WIDTH: i32 = 0x1c;
 pub const ABS_VOLUME: i32 = 0x20;
+pub const ABS_PROFILE: i32 = 0x21;
 pub const ABS_MISC: i32 = 0x28;
 pub const ABS_RESERVED: i32 = 0x2e;
 pub const ABS_MT_SLOT: i32 = 0x2f;
@@ -1,32 +1,747 @@
 use std::{
+    collections::BTreeMap,
     fs::File,
-    io::{self, BufReader},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    process,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use clap::Parser;
-use sendevent;
+use clap::{ArgEnum, Args, Parser, Subcommand};
+use sendevent::{
+    DeviceInfo, Discontinuity, Filter, Gesture, GestureGenerator, Jitter, Layout, MtProtocol,
+    Point, Radix, Recording, Rule, Script, SendOptions, Timestamp, Typist,
+};
+
+#[derive(Clone, ArgEnum)]
+enum TimestampArg {
+    Zero,
+    Monotonic,
+    Realtime,
+    Original,
+}
+
+impl From<TimestampArg> for Timestamp {
+    fn from(timestamp: TimestampArg) -> Self {
+        match timestamp {
+            TimestampArg::Zero => Timestamp::Zero,
+            TimestampArg::Monotonic => Timestamp::Monotonic,
+            TimestampArg::Realtime => Timestamp::Realtime,
+            TimestampArg::Original => Timestamp::Original,
+        }
+    }
+}
+
+#[derive(Clone, ArgEnum)]
+enum DiscontinuityArg {
+    Error,
+    Rebase,
+    Clamp,
+}
+
+impl From<DiscontinuityArg> for Discontinuity {
+    fn from(discontinuity: DiscontinuityArg) -> Self {
+        match discontinuity {
+            DiscontinuityArg::Error => Discontinuity::Error,
+            DiscontinuityArg::Rebase => Discontinuity::Rebase,
+            DiscontinuityArg::Clamp => Discontinuity::Clamp,
+        }
+    }
+}
+
+#[derive(Clone, ArgEnum)]
+enum ProtocolArg {
+    A,
+    B,
+}
+
+impl From<ProtocolArg> for MtProtocol {
+    fn from(protocol: ProtocolArg) -> Self {
+        match protocol {
+            ProtocolArg::A => MtProtocol::A,
+            ProtocolArg::B => MtProtocol::B,
+        }
+    }
+}
+
+#[derive(Clone, ArgEnum)]
+enum RadixArg {
+    Hex,
+    Decimal,
+}
+
+impl From<RadixArg> for Radix {
+    fn from(radix: RadixArg) -> Self {
+        match radix {
+            RadixArg::Hex => Radix::Hex,
+            RadixArg::Decimal => Radix::Decimal,
+        }
+    }
+}
+
+#[derive(Clone, ArgEnum)]
+enum LayoutArg {
+    Us,
+    De,
+}
+
+impl From<LayoutArg> for Layout {
+    fn from(layout: LayoutArg) -> Self {
+        match layout {
+            LayoutArg::Us => Layout::Us,
+            LayoutArg::De => Layout::De,
+        }
+    }
+}
 
 #[derive(Parser)]
-struct Args {
+#[clap(term_width = 120, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+    /// Options of `replay`, which runs when there is no subcommand
+    #[clap(flatten)]
+    replay: ReplayArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Write events to devices at the times they were recorded
+    Replay(ReplayArgs),
+    /// Write events in the format of getevent -l -t
+    Convert(ConvertArgs),
+    /// Write the events of a touch gesture to a device
+    Gesture(GestureArgs),
+    /// Type text as key presses
+    Type(TypeArgs),
+    /// Write the events of a script of taps, swipes, key presses and text
+    Script(ScriptArgs),
+    /// Summarize recordings as a script of taps, swipes and key presses
+    Decompile(DecompileArgs),
+    /// Report semantic problems in recordings
+    Validate(ValidateArgs),
+    /// Print statistics of recordings
+    Info(InfoArgs),
+    /// Draw the paths of touch contacts as an SVG image
+    Svg(SvgArgs),
+}
+
+#[derive(Args)]
+struct InputArgs {
+    #[clap(long)]
+    device: Option<String>,
+    #[clap(long = "path", value_name = "PATH[=DEVICE]")]
+    paths: Vec<String>,
+    #[clap(long)]
+    align_start: bool,
+    #[clap(long, value_name = "RULE")]
+    include: Vec<Rule>,
+    #[clap(long, value_name = "RULE")]
+    exclude: Vec<Rule>,
+    #[clap(long, arg_enum)]
+    protocol: Option<ProtocolArg>,
+    #[clap(long, value_name = "DURATION", parse(try_from_str = sendevent::parse_duration))]
+    interval: Option<Duration>,
+    #[clap(long, arg_enum)]
+    radix: Option<RadixArg>,
+}
+
+/// Splits `PATH[=DEVICE]` into the path and the device, which defaults to `device`. A path
+/// that exists is taken as is, even if it contains `=`.
+fn split_path<'a>(path: &'a str, device: Option<&'a str>) -> (&'a str, Option<&'a str>) {
+    if Path::new(path).exists() {
+        return (path, device);
+    }
+    match path.rsplit_once('=') {
+        Some((path, device)) => (path, Some(device)),
+        None => (path, device),
+    }
+}
+
+/// Opens the file at `path`, naming it in the error.
+fn open(path: &str) -> Result<File, sendevent::Error> {
+    File::open(path)
+        .map_err(|error| io::Error::new(error.kind(), format!("{}: {}", path, error)).into())
+}
+
+/// Creates the file at `path`, naming it in the error.
+fn create(path: &str) -> Result<File, sendevent::Error> {
+    File::create(path)
+        .map_err(|error| io::Error::new(error.kind(), format!("{}: {}", path, error)).into())
+}
+
+impl InputArgs {
+    /// Opens every `--path`, or stdin if there is none, with its path and default device.
+    fn open(&self) -> Vec<Input<'_>> {
+        let mut inputs: Vec<Input<'_>> = self
+            .paths
+            .iter()
+            .map(|path| {
+                let (path, device) = split_path(path, self.device.as_deref());
+                let reader: Box<dyn BufRead> = Box::new(BufReader::new(exit_on_error(open(path))));
+                (reader, Some(path), device)
+            })
+            .collect();
+        if inputs.is_empty() {
+            inputs.push((Box::new(io::stdin().lock()), None, self.device.as_deref()));
+        }
+        inputs
+    }
+
+    fn options(&self) -> SendOptions {
+        SendOptions {
+            align_start: self.align_start,
+            filter: Filter {
+                include: self.include.clone(),
+                exclude: self.exclude.clone(),
+            },
+            protocol: self.protocol.clone().map(Into::into),
+            interval: self.interval.unwrap_or_default(),
+            radix: self.radix.clone().map(Into::into).unwrap_or_default(),
+            ..SendOptions::default()
+        }
+    }
+}
+
+/// An opened input with its path, if it is not stdin, and its default device.
+type Input<'a> = (Box<dyn BufRead>, Option<&'a str>, Option<&'a str>);
+
+fn recordings<'a>(inputs: &'a mut [Input<'a>]) -> Vec<Recording<'a>> {
+    inputs
+        .iter_mut()
+        .map(|(reader, path, device)| Recording {
+            reader,
+            path: *path,
+            device: *device,
+        })
+        .collect()
+}
+
+#[derive(Args)]
+struct ReplayArgs {
+    #[clap(flatten)]
+    input: InputArgs,
+    #[clap(long, arg_enum, default_value = "zero")]
+    timestamp: TimestampArg,
+    #[clap(long, arg_enum, default_value = "clamp")]
+    discontinuity: DiscontinuityArg,
+    #[clap(long, value_name = "DURATION", parse(try_from_str = sendevent::parse_duration))]
+    max_gap: Option<Duration>,
+    /// Print the events and when they would be written instead of writing them
+    #[clap(long)]
+    dry_run: bool,
+    #[clap(long)]
+    report: bool,
+    #[clap(long, value_name = "PATH")]
+    report_json: Option<String>,
+    #[clap(
+        long,
+        value_name = "DURATION",
+        default_value = "5ms",
+        parse(try_from_str = sendevent::parse_duration)
+    )]
+    late_threshold: Duration,
+    #[clap(long, value_name = "DURATION", parse(try_from_str = sendevent::parse_duration))]
+    jitter: Option<Duration>,
+    #[clap(long, value_name = "UNITS")]
+    position_noise: Option<u32>,
+    #[clap(long, value_name = "PROBABILITY", parse(try_from_str = parse_probability))]
+    drop_moves: Option<f64>,
+    #[clap(long)]
+    seed: Option<u64>,
+}
+
+fn parse_probability(s: &str) -> Result<f64, String> {
+    match s.parse() {
+        Ok(probability) if (0.0..=1.0).contains(&probability) => Ok(probability),
+        _ => Err(format!("expected a probability from 0 to 1, not {:?}", s)),
+    }
+}
+
+#[derive(Args)]
+struct ConvertArgs {
+    #[clap(flatten)]
+    input: InputArgs,
+    #[clap(long, value_name = "PATH")]
+    output: Option<String>,
+}
+
+#[derive(Args)]
+struct DecompileArgs {
+    #[clap(flatten)]
+    input: InputArgs,
+    #[clap(long, value_name = "PATH")]
+    output: Option<String>,
+}
+
+#[derive(Args)]
+struct ValidateArgs {
     #[clap(long)]
     device: Option<String>,
+    #[clap(long = "path", value_name = "PATH[=DEVICE]")]
+    paths: Vec<String>,
+    #[clap(long, arg_enum)]
+    radix: Option<RadixArg>,
+    #[clap(long, value_name = "PATH")]
+    device_info: Option<String>,
+}
+
+#[derive(Args)]
+struct InfoArgs {
+    #[clap(flatten)]
+    input: InputArgs,
+    #[clap(
+        long,
+        value_name = "DURATION",
+        default_value = "1s",
+        parse(try_from_str = sendevent::parse_duration)
+    )]
+    gap: Duration,
     #[clap(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+struct SvgArgs {
+    #[clap(flatten)]
+    input: InputArgs,
+    #[clap(long, value_name = "PATH")]
+    device_info: Option<String>,
+    #[clap(long, value_name = "PIXELS", default_value = "540")]
+    width: u32,
+    #[clap(long, value_name = "PATH")]
+    output: Option<String>,
+}
+
+/// Parses samples per second, of which there can be at most one per microsecond.
+fn parse_rate(s: &str) -> Result<u32, String> {
+    match s.parse() {
+        Ok(rate) if (1..=1_000_000).contains(&rate) => Ok(rate),
+        _ => Err(format!("expected a rate from 1 to 1000000, not {:?}", s)),
+    }
+}
+
+#[derive(Args)]
+struct GestureArgs {
+    #[clap(long)]
+    device: String,
+    #[clap(long, value_name = "PATH")]
+    device_info: Option<String>,
+    #[clap(long, value_name = "DURATION", parse(try_from_str = sendevent::parse_duration))]
+    duration: Option<Duration>,
+    #[clap(long, default_value = "60", parse(try_from_str = parse_rate))]
+    rate: u32,
+    #[clap(long, arg_enum, default_value = "zero")]
+    timestamp: TimestampArg,
+    #[clap(long)]
+    dry_run: bool,
+    #[clap(subcommand)]
+    gesture: GestureCommand,
+}
+
+#[derive(Subcommand)]
+enum GestureCommand {
+    /// Touch a point briefly (default duration 100ms)
+    Tap { x: f64, y: f64 },
+    /// Tap a point twice (default duration 100ms per tap)
+    DoubleTap {
+        x: f64,
+        y: f64,
+        #[clap(
+            long,
+            value_name = "DURATION",
+            default_value = "100ms",
+            parse(try_from_str = sendevent::parse_duration)
+        )]
+        interval: Duration,
+    },
+    /// Touch a point and hold (default duration 1s)
+    LongPress { x: f64, y: f64 },
+    /// Move from one point to another (default duration 300ms)
+    Swipe {
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+        #[clap(long, number_of_values = 2, value_names = &["X", "Y"])]
+        control: Option<Vec<f64>>,
+    },
+    /// Move two fingers apart or together (default duration 500ms)
+    Pinch {
+        x: f64,
+        y: f64,
+        from: f64,
+        to: f64,
+        #[clap(long, default_value = "0", allow_hyphen_values = true)]
+        angle: f64,
+    },
+    /// Turn two fingers around a point, angles in degrees (default duration 500ms)
+    Rotate {
+        x: f64,
+        y: f64,
+        radius: f64,
+        #[clap(allow_hyphen_values = true)]
+        from: f64,
+        #[clap(allow_hyphen_values = true)]
+        to: f64,
+    },
+}
+
+impl GestureCommand {
+    fn gesture(&self, duration: Option<Duration>) -> Gesture {
+        let duration = |millis| duration.unwrap_or(Duration::from_millis(millis));
+        match *self {
+            GestureCommand::Tap { x, y } => Gesture::tap(Point { x, y }, duration(100)),
+            GestureCommand::DoubleTap { x, y, interval } => {
+                Gesture::double_tap(Point { x, y }, duration(100), interval)
+            }
+            GestureCommand::LongPress { x, y } => {
+                Gesture::long_press(Point { x, y }, duration(1000))
+            }
+            GestureCommand::Swipe {
+                x1,
+                y1,
+                x2,
+                y2,
+                ref control,
+            } => {
+                let from = Point { x: x1, y: y1 };
+                let to = Point { x: x2, y: y2 };
+                match control.as_deref() {
+                    Some(&[x, y]) => Gesture::curved_swipe(from, Point { x, y }, to, duration(300)),
+                    _ => Gesture::swipe(from, to, duration(300)),
+                }
+            }
+            GestureCommand::Pinch {
+                x,
+                y,
+                from,
+                to,
+                angle,
+            } => Gesture::pinch(Point { x, y }, from, to, angle, duration(500)),
+            GestureCommand::Rotate {
+                x,
+                y,
+                radius,
+                from,
+                to,
+            } => Gesture::rotate(Point { x, y }, radius, from, to, duration(500)),
+        }
+    }
+}
+
+#[derive(Args)]
+struct TypeArgs {
+    #[clap(long)]
+    device: String,
+    #[clap(long, arg_enum, default_value = "us")]
+    layout: LayoutArg,
+    #[clap(
+        long,
+        value_name = "DURATION",
+        default_value = "100ms",
+        parse(try_from_str = sendevent::parse_duration)
+    )]
+    delay: Duration,
+    #[clap(long, arg_enum, default_value = "zero")]
+    timestamp: TimestampArg,
+    #[clap(long)]
+    dry_run: bool,
+    text: String,
+}
+
+#[derive(Args)]
+struct ScriptArgs {
+    #[clap(long, value_name = "PATH")]
     path: Option<String>,
+    #[clap(long)]
+    device: Option<String>,
+    #[clap(long, value_name = "PATH")]
+    device_info: Option<String>,
+    #[clap(long, arg_enum, default_value = "zero")]
+    timestamp: TimestampArg,
+    #[clap(long)]
+    dry_run: bool,
+    #[clap(long, conflicts_with = "dry-run")]
+    compile: bool,
+}
+
+fn exit_on_error<T>(result: Result<T, sendevent::Error>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            process::exit(1);
+        }
+    }
+}
+
+/// Reads the capabilities of every device from the output of `getevent -p` at `path`, if any.
+fn all_device_info(path: Option<&str>) -> BTreeMap<String, DeviceInfo> {
+    match path {
+        Some(path) => exit_on_error(
+            open(path).and_then(|file| DeviceInfo::all_from_getevent(&mut BufReader::new(file))),
+        ),
+        None => BTreeMap::new(),
+    }
+}
+
+fn replay(args: ReplayArgs) {
+    let mut jitter = Jitter {
+        seed: args.seed.unwrap_or(0),
+        time: args.jitter.unwrap_or_default(),
+        position: args.position_noise.unwrap_or_default(),
+        drop: args.drop_moves.unwrap_or_default(),
+    };
+    if args.seed.is_none() && !jitter.is_empty() {
+        jitter.seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64;
+        eprintln!("perturbing with --seed {}", jitter.seed);
+    }
+    let options = SendOptions {
+        timestamp: args.timestamp.into(),
+        discontinuity: args.discontinuity.into(),
+        max_gap: args.max_gap,
+        jitter,
+        ..args.input.options()
+    };
+    let mut inputs = args.input.open();
+    let recordings = recordings(&mut inputs);
+    if args.dry_run {
+        let stdout = io::stdout();
+        exit_on_error(sendevent::dry_run_from_recordings(
+            recordings,
+            &options,
+            &mut stdout.lock(),
+        ));
+    } else {
+        // Created before replaying, so that a bad path fails before the events are written.
+        let mut report_json = args
+            .report_json
+            .as_deref()
+            .map(|path| exit_on_error(create(path)));
+        let report = exit_on_error(sendevent::send_events_from_recordings(recordings, &options));
+        if args.report {
+            let stderr = io::stderr();
+            exit_on_error(
+                report
+                    .write_text(&mut stderr.lock(), args.late_threshold)
+                    .map_err(Into::into),
+            );
+        }
+        if let Some(file) = &mut report_json {
+            exit_on_error(
+                report
+                    .write_json(file, args.late_threshold)
+                    .map_err(Into::into),
+            );
+        }
+    }
+}
+
+/// Opens `path`, or stdout if it is `None`.
+fn output(path: Option<String>) -> Box<dyn Write> {
+    if let Some(path) = path {
+        Box::new(BufWriter::new(exit_on_error(create(&path))))
+    } else {
+        Box::new(io::stdout().lock())
+    }
+}
+
+fn convert(args: ConvertArgs) {
+    let options = args.input.options();
+    let mut inputs = args.input.open();
+    let recordings = recordings(&mut inputs);
+    let mut output = output(args.output);
+    exit_on_error(sendevent::convert_recordings(
+        recordings,
+        &options,
+        &mut output,
+    ));
+    exit_on_error(output.flush().map_err(Into::into));
+}
+
+fn decompile(args: DecompileArgs) {
+    let options = args.input.options();
+    let mut inputs = args.input.open();
+    let recordings = recordings(&mut inputs);
+    let mut output = output(args.output);
+    exit_on_error(sendevent::decompile_recordings(
+        recordings,
+        &options,
+        &mut output,
+    ));
+    exit_on_error(output.flush().map_err(Into::into));
+}
+
+fn validate(args: ValidateArgs) {
+    let device_info = all_device_info(args.device_info.as_deref());
+    let radix = args.radix.map(Into::into).unwrap_or_default();
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let mut count = 0;
+    let mut report = |name: Option<&str>, problems: Vec<sendevent::Problem>| {
+        count += problems.len();
+        for problem in problems {
+            match name {
+                Some(name) => writeln!(stdout, "{}: {}", name, problem).unwrap(),
+                None => writeln!(stdout, "{}", problem).unwrap(),
+            }
+        }
+    };
+    for path in &args.paths {
+        let (path, device) = split_path(path, args.device.as_deref());
+        let mut reader = BufReader::new(exit_on_error(open(path)));
+        report(
+            Some(path),
+            exit_on_error(sendevent::validate_recording(
+                &mut reader,
+                device,
+                radix,
+                &device_info,
+            )),
+        );
+    }
+    if args.paths.is_empty() {
+        report(
+            None,
+            exit_on_error(sendevent::validate_recording(
+                &mut io::stdin().lock(),
+                args.device.as_deref(),
+                radix,
+                &device_info,
+            )),
+        );
+    }
+    if count > 0 {
+        eprintln!("{} problems found", count);
+        process::exit(1);
+    }
+}
+
+fn info(args: InfoArgs) {
+    let options = args.input.options();
+    let mut inputs = args.input.open();
+    let recordings = recordings(&mut inputs);
+    let info = exit_on_error(sendevent::info_from_recordings(
+        recordings, &options, args.gap,
+    ));
+    let stdout = io::stdout();
+    if args.json {
+        info.write_json(&mut stdout.lock()).unwrap();
+    } else {
+        info.write_text(&mut stdout.lock()).unwrap();
+    }
+}
+
+fn svg(args: SvgArgs) {
+    let options = args.input.options();
+    let device_info = all_device_info(args.device_info.as_deref());
+    let mut inputs = args.input.open();
+    let recordings = recordings(&mut inputs);
+    let mut output = output(args.output);
+    exit_on_error(sendevent::render_svg(
+        recordings,
+        &options,
+        &device_info,
+        args.width,
+        &mut output,
+    ));
+    exit_on_error(output.flush().map_err(Into::into));
+}
+
+fn gesture(args: GestureArgs) {
+    let info = exit_on_error(if let Some(path) = &args.device_info {
+        open(path)
+            .and_then(|file| DeviceInfo::from_getevent(&mut BufReader::new(file), &args.device))
+    } else {
+        DeviceInfo::query(&args.device)
+    });
+    let mut generator = GestureGenerator::new(&args.device, info);
+    generator.rate = args.rate;
+    let gesture = args.gesture.gesture(args.duration);
+    let options = SendOptions {
+        timestamp: args.timestamp.into(),
+        ..SendOptions::default()
+    };
+
+    if args.dry_run {
+        let stdout = io::stdout();
+        exit_on_error(sendevent::dry_run_gesture(
+            &mut generator,
+            &gesture,
+            &options,
+            &mut stdout.lock(),
+        ));
+    } else {
+        exit_on_error(sendevent::send_gesture(&mut generator, &gesture, &options));
+    }
+}
+
+fn type_text(args: TypeArgs) {
+    let mut typist = Typist::new(&args.device, args.layout.into());
+    typist.delay = args.delay;
+    let options = SendOptions {
+        timestamp: args.timestamp.into(),
+        ..SendOptions::default()
+    };
+
+    if args.dry_run {
+        let stdout = io::stdout();
+        exit_on_error(sendevent::dry_run_text(
+            &typist,
+            &args.text,
+            &options,
+            &mut stdout.lock(),
+        ));
+    } else {
+        exit_on_error(sendevent::send_text(&typist, &args.text, &options));
+    }
+}
+
+fn script(args: ScriptArgs) {
+    let mut script = Script::new(args.device.as_deref());
+    script.device_info = all_device_info(args.device_info.as_deref())
+        .into_iter()
+        .collect();
+    exit_on_error(if let Some(path) = &args.path {
+        script.read_file(Path::new(path))
+    } else {
+        script.read(&mut io::stdin().lock(), Path::new(""))
+    });
+    let options = SendOptions {
+        timestamp: args.timestamp.into(),
+        ..SendOptions::default()
+    };
+
+    let stdout = io::stdout();
+    if args.dry_run {
+        exit_on_error(sendevent::dry_run_script(
+            script,
+            &options,
+            &mut stdout.lock(),
+        ));
+    } else if args.compile {
+        exit_on_error(sendevent::convert_script(script, &mut stdout.lock()));
+    } else {
+        exit_on_error(sendevent::send_script(script, &options));
+    }
 }
 
 fn main() {
-    let args = Args::parse();
-    let device = args
-        .device
-        .as_ref()
-        .and_then(|device| Some(device.as_str()));
-    if let Some(path) = args.path {
-        let file = File::open(path).unwrap();
-        let mut reader = BufReader::new(file);
-        sendevent::send_events_from_reader(&mut reader, device);
-    } else {
-        let stdin = io::stdin();
-        let mut reader = stdin.lock();
-        sendevent::send_events_from_reader(&mut reader, device);
+    let cli = Cli::parse();
+    match cli.command {
+        None => replay(cli.replay),
+        Some(Command::Replay(args)) => replay(args),
+        Some(Command::Convert(args)) => convert(args),
+        Some(Command::Gesture(args)) => gesture(args),
+        Some(Command::Type(args)) => type_text(args),
+        Some(Command::Script(args)) => script(args),
+        Some(Command::Decompile(args)) => decompile(args),
+        Some(Command::Validate(args)) => validate(args),
+        Some(Command::Info(args)) => info(args),
+        Some(Command::Svg(args)) => svg(args),
     }
 }
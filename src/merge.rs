@@ -0,0 +1,138 @@
+use std::iter::Peekable;
+use std::time::Duration;
+
+use crate::{Error, Packet, TimeVal};
+
+/// Merges packets of several recordings in the order of their timestamps.
+pub(crate) struct Merge<I: Iterator<Item = Result<Packet, Error>>> {
+    streams: Vec<Peekable<I>>,
+    /// Time subtracted from the packets of each stream, so that aligned streams start at zero.
+    bases: Vec<Duration>,
+}
+
+impl<I: Iterator<Item = Result<Packet, Error>>> Merge<I> {
+    pub(crate) fn new(streams: Vec<I>, align_start: bool) -> Self {
+        let mut streams: Vec<_> = streams.into_iter().map(Iterator::peekable).collect();
+        let bases = streams
+            .iter_mut()
+            .map(|stream| match stream.peek() {
+                Some(Ok(packet)) if align_start => packet.time(),
+                _ => Duration::ZERO,
+            })
+            .collect();
+        Merge { streams, bases }
+    }
+}
+
+impl<I: Iterator<Item = Result<Packet, Error>>> Iterator for Merge<I> {
+    type Item = Result<Packet, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Errors sort first so that they are reported as soon as they are encountered.
+        let (_, index) = self
+            .streams
+            .iter_mut()
+            .zip(&self.bases)
+            .enumerate()
+            .filter_map(|(index, (stream, base))| match stream.peek()? {
+                Ok(packet) => Some((Some(packet.time().saturating_sub(*base)), index)),
+                Err(_) => Some((None, index)),
+            })
+            .min()?;
+        let base = self.bases[index];
+        Some(self.streams[index].next()?.map(|mut packet| {
+            if !base.is_zero() {
+                for event in &mut packet.events {
+                    event.time =
+                        TimeVal::from_duration(event.time.to_duration().saturating_sub(base));
+                }
+            }
+            packet
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Merge;
+    use crate::{packets, parse_all};
+
+    const TOUCH: &str = "\
+[     100.000000] EV_SYN SYN_REPORT 00000000
+[     100.300000] EV_SYN SYN_REPORT 00000000
+";
+    const KEYS: &str = "\
+[     100.100000] EV_SYN SYN_REPORT 00000000
+[     100.200000] EV_SYN SYN_REPORT 00000000
+[     100.400000] EV_SYN SYN_REPORT 00000000
+";
+    const SENSORS: &str = "\
+[      50.000000] EV_SYN SYN_REPORT 00000000
+[      50.250000] EV_SYN SYN_REPORT 00000000
+";
+
+    fn merge(align_start: bool) -> Vec<(String, u128)> {
+        let mut touch = TOUCH.as_bytes();
+        let mut keys = KEYS.as_bytes();
+        let mut sensors = SENSORS.as_bytes();
+        Merge::new(
+            vec![
+                packets(parse_all(&mut touch), Some("touch")),
+                packets(parse_all(&mut keys), Some("keys")),
+                packets(parse_all(&mut sensors), Some("sensors")),
+            ],
+            align_start,
+        )
+        .map(|packet| {
+            let packet = packet.unwrap();
+            (packet.device.clone(), packet.time().as_millis())
+        })
+        .collect()
+    }
+
+    #[test]
+    fn absolute() {
+        assert_eq!(
+            merge(false),
+            [
+                ("sensors".to_string(), 50000),
+                ("sensors".to_string(), 50250),
+                ("touch".to_string(), 100000),
+                ("keys".to_string(), 100100),
+                ("keys".to_string(), 100200),
+                ("touch".to_string(), 100300),
+                ("keys".to_string(), 100400),
+            ]
+        );
+    }
+
+    #[test]
+    fn align_start() {
+        assert_eq!(
+            merge(true),
+            [
+                ("touch".to_string(), 0),
+                ("keys".to_string(), 0),
+                ("sensors".to_string(), 0),
+                ("keys".to_string(), 100),
+                ("sensors".to_string(), 250),
+                ("touch".to_string(), 300),
+                ("keys".to_string(), 300),
+            ]
+        );
+    }
+
+    #[test]
+    fn error() {
+        let mut good = TOUCH.as_bytes();
+        let mut bad = "[     200.000000] EV_SYN\n".as_bytes();
+        let mut merge = Merge::new(
+            vec![
+                packets(parse_all(&mut good), Some("good")),
+                packets(parse_all(&mut bad), Some("bad")),
+            ],
+            false,
+        );
+        assert!(merge.next().unwrap().is_err());
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::BTreeMap;
+
+use crate::linux::input_event_codes::*;
+use crate::Packet;
+
+/// A contact that touches a device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Touch {
+    pub slot: i32,
+    pub tracking_id: i32,
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Contacts of one touch device, tracked from its events.
+///
+/// Devices that report multitouch protocol B are tracked by their slots. Other devices are
+/// tracked as a single contact in slot 0 from `BTN_TOUCH`, `ABS_X` and `ABS_Y`, with tracking
+/// IDs that count the touches. Like the kernel asks clients to, events from `SYN_DROPPED` up to
+/// and including the next `SYN_REPORT` are discarded.
+#[derive(Debug, Clone, Default)]
+pub struct MtState {
+    multitouch: bool,
+    slot: i32,
+    /// Slots as of the last event, with a tracking ID of `-1` if they are not in use.
+    slots: BTreeMap<i32, Touch>,
+    touches: i32,
+    /// `BTN_TOOL_*` key that is down as of the last event.
+    pending_tool: Option<u16>,
+    dropped: bool,
+    /// Contacts and tool as of the last `SYN_REPORT`.
+    contacts: Vec<Touch>,
+    tool: Option<u16>,
+}
+
+impl MtState {
+    /// Updates the state with an event. Returns whether the event is a `SYN_REPORT` that
+    /// updated the contacts.
+    pub fn event(&mut self, r#type: u16, code: u16, value: i32) -> bool {
+        let (r#type, code) = (i32::from(r#type), i32::from(code));
+        if self.dropped {
+            if (r#type, code) == (EV_SYN, SYN_REPORT) {
+                // Roll back the events of the incomplete packet.
+                self.dropped = false;
+                self.pending_tool = self.tool;
+                self.slots = self
+                    .contacts
+                    .iter()
+                    .map(|&touch| (touch.slot, touch))
+                    .collect();
+            }
+            return false;
+        }
+        match (r#type, code) {
+            (EV_SYN, SYN_REPORT) => {
+                self.contacts = self
+                    .slots
+                    .values()
+                    .filter(|touch| touch.tracking_id != -1)
+                    .copied()
+                    .collect();
+                self.tool = self.pending_tool;
+                return true;
+            }
+            (EV_SYN, SYN_DROPPED) => self.dropped = true,
+            (EV_ABS, ABS_MT_SLOT) => {
+                self.multitouch = true;
+                self.slot = value;
+            }
+            (EV_ABS, ABS_MT_TRACKING_ID) => {
+                self.multitouch = true;
+                self.touch(self.slot).tracking_id = value;
+            }
+            (EV_ABS, ABS_MT_POSITION_X) => self.touch(self.slot).x = value,
+            (EV_ABS, ABS_MT_POSITION_Y) => self.touch(self.slot).y = value,
+            (EV_ABS, ABS_X) if !self.multitouch => self.touch(0).x = value,
+            (EV_ABS, ABS_Y) if !self.multitouch => self.touch(0).y = value,
+            (EV_KEY, BTN_TOUCH) if !self.multitouch => {
+                let touches = self.touches;
+                let touch = self.touch(0);
+                if value == 0 {
+                    touch.tracking_id = -1;
+                } else if touch.tracking_id == -1 {
+                    touch.tracking_id = touches;
+                    self.touches += 1;
+                }
+            }
+            (EV_KEY, BTN_TOOL_PEN..=BTN_TOOL_QUINTTAP | BTN_TOOL_DOUBLETAP..=BTN_TOOL_QUADTAP) => {
+                let code = code as u16;
+                if value != 0 {
+                    self.pending_tool = Some(code);
+                } else if self.pending_tool == Some(code) {
+                    self.pending_tool = None;
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Updates the state with the events of a packet.
+    pub(crate) fn packet(&mut self, packet: &Packet) {
+        for event in &packet.events {
+            self.event(event.r#type, event.code, event.value);
+        }
+    }
+
+    fn touch(&mut self, slot: i32) -> &mut Touch {
+        self.slots.entry(slot).or_insert(Touch {
+            slot,
+            tracking_id: -1,
+            x: 0,
+            y: 0,
+        })
+    }
+
+    /// Contacts that touched at the last `SYN_REPORT`, ordered by slot.
+    pub fn contacts(&self) -> &[Touch] {
+        &self.contacts
+    }
+
+    /// `BTN_TOOL_*` key that was down at the last `SYN_REPORT`, such as `BTN_TOOL_FINGER` or
+    /// `BTN_TOOL_DOUBLETAP`.
+    pub fn tool(&self) -> Option<u16> {
+        self.tool
+    }
+
+    /// Whether the device reports multitouch protocol B.
+    pub fn is_multitouch(&self) -> bool {
+        self.multitouch
+    }
+
+    /// Slot selected by the last `ABS_MT_SLOT` event.
+    pub fn slot(&self) -> i32 {
+        self.slot
+    }
+
+    /// Whether a slot has a contact as of the last event, which may not have been reported yet.
+    pub(crate) fn is_touching(&self, slot: i32) -> bool {
+        self.slots
+            .get(&slot)
+            .is_some_and(|touch| touch.tracking_id != -1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MtState, Touch};
+    use crate::linux::input_event_codes::*;
+    use crate::{packets, parse_all};
+
+    /// Contacts and tool after each packet of a recording of one device.
+    fn track(input: &str) -> Vec<(Vec<Touch>, Option<u16>)> {
+        let mut state = MtState::default();
+        packets(parse_all(&mut input.as_bytes()), None)
+            .map(|packet| {
+                state.packet(&packet.unwrap());
+                (state.contacts().to_vec(), state.tool())
+            })
+            .collect()
+    }
+
+    fn touch(slot: i32, tracking_id: i32, x: i32, y: i32) -> Touch {
+        Touch {
+            slot,
+            tracking_id,
+            x,
+            y,
+        }
+    }
+
+    #[test]
+    fn multitouch() {
+        // Two fingers on the touchscreen of a phone, with a `SYN_DROPPED` while the first one
+        // moves, in which the second one lifts.
+        let input = "\
+[   14508.298422] /dev/input/event2: EV_ABS       ABS_MT_TRACKING_ID   00000a4e
+[   14508.298422] /dev/input/event2: EV_ABS       ABS_MT_POSITION_X    000001dc
+[   14508.298422] /dev/input/event2: EV_ABS       ABS_MT_POSITION_Y    00000514
+[   14508.298422] /dev/input/event2: EV_ABS       ABS_MT_TOUCH_MAJOR   00000005
+[   14508.298422] /dev/input/event2: EV_ABS       ABS_MT_PRESSURE      0000002c
+[   14508.298422] /dev/input/event2: EV_KEY       BTN_TOUCH            DOWN
+[   14508.298422] /dev/input/event2: EV_KEY       BTN_TOOL_FINGER      DOWN
+[   14508.298422] /dev/input/event2: EV_SYN       SYN_REPORT           00000000
+[   14508.306722] /dev/input/event2: EV_ABS       ABS_MT_SLOT          00000001
+[   14508.306722] /dev/input/event2: EV_ABS       ABS_MT_TRACKING_ID   00000a4f
+[   14508.306722] /dev/input/event2: EV_ABS       ABS_MT_POSITION_X    00000302
+[   14508.306722] /dev/input/event2: EV_ABS       ABS_MT_POSITION_Y    000003a0
+[   14508.306722] /dev/input/event2: EV_ABS       ABS_MT_TOUCH_MAJOR   00000004
+[   14508.306722] /dev/input/event2: EV_ABS       ABS_MT_PRESSURE      00000028
+[   14508.306722] /dev/input/event2: EV_KEY       BTN_TOOL_FINGER      UP
+[   14508.306722] /dev/input/event2: EV_KEY       BTN_TOOL_DOUBLETAP   DOWN
+[   14508.306722] /dev/input/event2: EV_SYN       SYN_REPORT           00000000
+[   14508.315043] /dev/input/event2: EV_ABS       ABS_MT_SLOT          00000000
+[   14508.315043] /dev/input/event2: EV_ABS       ABS_MT_POSITION_X    000001e1
+[   14508.315043] /dev/input/event2: EV_SYN       SYN_DROPPED          00000000
+[   14508.323365] /dev/input/event2: EV_ABS       ABS_MT_SLOT          00000001
+[   14508.323365] /dev/input/event2: EV_ABS       ABS_MT_TRACKING_ID   ffffffff
+[   14508.323365] /dev/input/event2: EV_SYN       SYN_REPORT           00000000
+[   14508.331690] /dev/input/event2: EV_ABS       ABS_MT_SLOT          00000000
+[   14508.331690] /dev/input/event2: EV_ABS       ABS_MT_POSITION_X    000001e6
+[   14508.331690] /dev/input/event2: EV_ABS       ABS_MT_POSITION_Y    0000050f
+[   14508.331690] /dev/input/event2: EV_SYN       SYN_REPORT           00000000
+[   14508.340011] /dev/input/event2: EV_ABS       ABS_MT_SLOT          00000001
+[   14508.340011] /dev/input/event2: EV_ABS       ABS_MT_TRACKING_ID   ffffffff
+[   14508.340011] /dev/input/event2: EV_KEY       BTN_TOOL_FINGER      DOWN
+[   14508.340011] /dev/input/event2: EV_KEY       BTN_TOOL_DOUBLETAP   UP
+[   14508.340011] /dev/input/event2: EV_SYN       SYN_REPORT           00000000
+[   14508.348336] /dev/input/event2: EV_ABS       ABS_MT_SLOT          00000000
+[   14508.348336] /dev/input/event2: EV_ABS       ABS_MT_TRACKING_ID   ffffffff
+[   14508.348336] /dev/input/event2: EV_KEY       BTN_TOUCH            UP
+[   14508.348336] /dev/input/event2: EV_KEY       BTN_TOOL_FINGER      UP
+[   14508.348336] /dev/input/event2: EV_SYN       SYN_REPORT           00000000
+";
+        let first = touch(0, 0xa4e, 0x1dc, 0x514);
+        let second = touch(1, 0xa4f, 0x302, 0x3a0);
+        let finger = Some(BTN_TOOL_FINGER as u16);
+        let doubletap = Some(BTN_TOOL_DOUBLETAP as u16);
+        assert_eq!(
+            track(input),
+            [
+                (vec![first], finger),
+                (vec![first, second], doubletap),
+                (vec![first, second], doubletap),
+                (vec![touch(0, 0xa4e, 0x1e6, 0x50f), second], doubletap),
+                (vec![touch(0, 0xa4e, 0x1e6, 0x50f)], finger),
+                (vec![], None),
+            ]
+        );
+    }
+
+    #[test]
+    fn single_touch() {
+        // A resistive touchscreen without multitouch.
+        let input = "\
+[     312.660331] /dev/input/event1: EV_ABS       ABS_X                00000e3b
+[     312.660331] /dev/input/event1: EV_ABS       ABS_Y                0000052c
+[     312.660331] /dev/input/event1: EV_ABS       ABS_PRESSURE         000000b4
+[     312.660331] /dev/input/event1: EV_KEY       BTN_TOUCH            DOWN
+[     312.660331] /dev/input/event1: EV_SYN       SYN_REPORT           00000000
+[     312.670318] /dev/input/event1: EV_ABS       ABS_X                00000e30
+[     312.670318] /dev/input/event1: EV_SYN       SYN_REPORT           00000000
+[     312.690402] /dev/input/event1: EV_ABS       ABS_PRESSURE         00000000
+[     312.690402] /dev/input/event1: EV_KEY       BTN_TOUCH            UP
+[     312.690402] /dev/input/event1: EV_SYN       SYN_REPORT           00000000
+[     313.120044] /dev/input/event1: EV_ABS       ABS_X                0000034d
+[     313.120044] /dev/input/event1: EV_ABS       ABS_Y                00000b02
+[     313.120044] /dev/input/event1: EV_KEY       BTN_TOUCH            DOWN
+[     313.120044] /dev/input/event1: EV_SYN       SYN_REPORT           00000000
+";
+        assert_eq!(
+            track(input),
+            [
+                (vec![touch(0, 0, 0xe3b, 0x52c)], None),
+                (vec![touch(0, 0, 0xe30, 0x52c)], None),
+                (vec![], None),
+                (vec![touch(0, 1, 0x34d, 0xb02)], None),
+            ]
+        );
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::{BTreeMap, HashMap};
+use std::mem;
+
+use crate::linux::input_event_codes::*;
+use crate::{is_syn_report, InputEvent, Packet};
+
+/// Multitouch protocol of a device, see
+/// <https://www.kernel.org/doc/html/latest/input/multi-touch-protocol.html>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MtProtocol {
+    /// Anonymous contacts, all of which are reported in every packet, each terminated by
+    /// `SYN_MT_REPORT`.
+    A,
+    /// Contacts in slots selected by `ABS_MT_SLOT` and identified by `ABS_MT_TRACKING_ID`, of
+    /// which only changes are reported.
+    B,
+}
+
+/// Values of the `ABS_MT_*` axes of a contact by code, except `ABS_MT_SLOT`.
+type Contact = BTreeMap<u16, i32>;
+
+const TRACKING_ID: u16 = ABS_MT_TRACKING_ID as u16;
+
+fn is_mt(event: &InputEvent) -> bool {
+    i32::from(event.r#type) == EV_ABS
+        && (ABS_MT_TOUCH_MAJOR..=ABS_MAX).contains(&i32::from(event.code))
+}
+
+fn is_event(event: &InputEvent, r#type: i32, code: i32) -> bool {
+    i32::from(event.r#type) == r#type && i32::from(event.code) == code
+}
+
+fn squared_distance(a: &Contact, b: &Contact) -> i64 {
+    [ABS_MT_POSITION_X, ABS_MT_POSITION_Y]
+        .iter()
+        .map(|&code| {
+            let code = code as u16;
+            let d = i64::from(*a.get(&code).unwrap_or(&0)) - i64::from(*b.get(&code).unwrap_or(&0));
+            d * d
+        })
+        .sum()
+}
+
+/// State of one device while converting.
+#[derive(Debug, Default)]
+struct DeviceState {
+    /// Whether the device uses the protocol that is converted from.
+    converting: bool,
+    /// Contacts by slot. Lifted contacts of protocol B keep their values without a tracking ID,
+    /// because the kernel only reports values that differ from them.
+    slots: BTreeMap<i32, Contact>,
+    /// Slot selected by the last `ABS_MT_SLOT` event.
+    slot: i32,
+    next_tracking_id: i32,
+    /// Whether the last packet of protocol A reported any contacts.
+    reported: bool,
+}
+
+impl DeviceState {
+    /// Assigns the contacts of a packet of protocol A to slots. Contacts with a tracking ID keep
+    /// the slot of the contact with the same ID, the others the slot of the closest contact of
+    /// the previous packet. Slots of contacts that are gone are lifted, which is given as `None`.
+    fn assign(&mut self, contacts: Vec<Contact>) -> BTreeMap<i32, Option<Contact>> {
+        let mut free: Vec<i32> = self.slots.keys().copied().collect();
+        let mut assigned = BTreeMap::new();
+        let mut unmatched = vec![];
+        for contact in contacts {
+            let slot = contact.get(&TRACKING_ID).and_then(|id| {
+                free.iter()
+                    .position(|slot| self.slots[slot].get(&TRACKING_ID) == Some(id))
+            });
+            match slot {
+                Some(index) => {
+                    assigned.insert(free.remove(index), Some(contact));
+                }
+                None => unmatched.push(Some(contact)),
+            }
+        }
+
+        let mut pairs = vec![];
+        for (index, contact) in unmatched.iter().enumerate() {
+            let contact = contact.as_ref().unwrap();
+            if !contact.contains_key(&TRACKING_ID) {
+                for &slot in &free {
+                    pairs.push((squared_distance(&self.slots[&slot], contact), index, slot));
+                }
+            }
+        }
+        pairs.sort_unstable();
+        for (_, index, slot) in pairs {
+            if free.contains(&slot) && unmatched[index].is_some() {
+                let mut contact = unmatched[index].take().unwrap();
+                contact.insert(TRACKING_ID, self.slots[&slot][&TRACKING_ID]);
+                assigned.insert(slot, Some(contact));
+                free.retain(|&free| free != slot);
+            }
+        }
+
+        for slot in free {
+            assigned.insert(slot, None);
+        }
+        for mut contact in unmatched.into_iter().flatten() {
+            let slot = (0..)
+                .find(|slot| !self.slots.contains_key(slot) && !assigned.contains_key(slot))
+                .unwrap();
+            contact.entry(TRACKING_ID).or_insert_with(|| {
+                let id = self.next_tracking_id;
+                self.next_tracking_id = (id + 1) & 0xffff;
+                id
+            });
+            assigned.insert(slot, Some(contact));
+        }
+        assigned
+    }
+
+    /// Events of protocol B for a packet of protocol A, followed by its other events.
+    fn a_to_b(&mut self, packet: &Packet) -> Vec<(i32, i32, i32)> {
+        let mut contacts = vec![];
+        let mut contact = Contact::new();
+        let mut other = vec![];
+        for event in &packet.events {
+            if is_event(event, EV_SYN, SYN_MT_REPORT) {
+                if !contact.is_empty() {
+                    contacts.push(mem::take(&mut contact));
+                }
+            } else if is_mt(event) {
+                contact.insert(event.code, event.value);
+            } else if !is_syn_report(event) {
+                other.push((event.r#type.into(), event.code.into(), event.value));
+            }
+        }
+        if !contact.is_empty() {
+            contacts.push(contact);
+        }
+
+        let mut events = vec![];
+        for (slot, contact) in self.assign(contacts) {
+            if slot != self.slot {
+                events.push((EV_ABS, ABS_MT_SLOT, slot));
+                self.slot = slot;
+            }
+            match contact {
+                None => {
+                    events.push((EV_ABS, ABS_MT_TRACKING_ID, -1));
+                    self.slots.remove(&slot);
+                }
+                Some(contact) => {
+                    let previous = self.slots.entry(slot).or_default();
+                    // The tracking ID of a new contact comes first.
+                    let values = contact
+                        .get_key_value(&TRACKING_ID)
+                        .into_iter()
+                        .chain(contact.iter().filter(|(&code, _)| code != TRACKING_ID));
+                    for (&code, &value) in values {
+                        if previous.get(&code) != Some(&value) {
+                            events.push((EV_ABS, code.into(), value));
+                        }
+                    }
+                    *previous = contact;
+                }
+            }
+        }
+        events.extend(other);
+        events
+    }
+
+    /// Events of protocol A for a packet of protocol B, followed by its other events.
+    fn b_to_a(&mut self, packet: &Packet) -> Vec<(i32, i32, i32)> {
+        let mut other = vec![];
+        for event in &packet.events {
+            if is_event(event, EV_ABS, ABS_MT_SLOT) {
+                self.slot = event.value;
+            } else if is_event(event, EV_ABS, ABS_MT_TRACKING_ID) && event.value == -1 {
+                if let Some(contact) = self.slots.get_mut(&self.slot) {
+                    contact.remove(&TRACKING_ID);
+                }
+            } else if is_mt(event) {
+                self.slots
+                    .entry(self.slot)
+                    .or_default()
+                    .insert(event.code, event.value);
+            } else if !is_syn_report(event) {
+                other.push((event.r#type.into(), event.code.into(), event.value));
+            }
+        }
+
+        let mut events = vec![];
+        for contact in self.slots.values() {
+            if contact.contains_key(&TRACKING_ID) {
+                events.extend(
+                    contact
+                        .iter()
+                        .map(|(&code, &value)| (EV_ABS, code.into(), value)),
+                );
+                events.push((EV_SYN, SYN_MT_REPORT, 0));
+            }
+        }
+        let reported = !events.is_empty();
+        // A single `SYN_MT_REPORT` reports that the last contact was lifted.
+        if !reported && self.reported {
+            events.push((EV_SYN, SYN_MT_REPORT, 0));
+        }
+        self.reported = reported;
+        events.extend(other);
+        events
+    }
+}
+
+/// Rewrites multitouch events of one protocol as the other. Devices that never send an event of
+/// the other protocol, and packets that are not terminated by `SYN_REPORT`, are left alone.
+///
+/// Protocol A is converted to B by assigning contacts to slots, matching them with the contacts
+/// of the previous packet by their tracking IDs, if any, or else by distance. New contacts get
+/// the lowest free slot and the next tracking ID.
+#[derive(Debug)]
+pub(crate) struct Converter {
+    protocol: MtProtocol,
+    devices: HashMap<String, DeviceState>,
+}
+
+impl Converter {
+    pub(crate) fn new(protocol: MtProtocol) -> Self {
+        Converter {
+            protocol,
+            devices: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn apply(&mut self, packet: Packet) -> Packet {
+        let state = self.devices.entry(packet.device.clone()).or_default();
+        state.converting |= match self.protocol {
+            MtProtocol::A => {
+                packet
+                    .events
+                    .iter()
+                    .any(|event| is_mt(event) || is_event(event, EV_ABS, ABS_MT_SLOT))
+                    && !packet
+                        .events
+                        .iter()
+                        .any(|event| is_event(event, EV_SYN, SYN_MT_REPORT))
+            }
+            MtProtocol::B => packet
+                .events
+                .iter()
+                .any(|event| is_event(event, EV_SYN, SYN_MT_REPORT)),
+        };
+        if !state.converting || !packet.events.last().is_some_and(is_syn_report) {
+            return packet;
+        }
+        let events = match self.protocol {
+            MtProtocol::A => state.b_to_a(&packet),
+            MtProtocol::B => state.a_to_b(&packet),
+        };
+        Packet {
+            line: packet.line,
+            ..Packet::new(&packet.device, packet.time(), events)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Converter, MtProtocol};
+    use crate::{convert_packets, packets, parse_all};
+
+    fn convert(input: &str, protocol: MtProtocol) -> String {
+        let mut converter = Converter::new(protocol);
+        let mut output = vec![];
+        convert_packets(
+            packets(parse_all(&mut input.as_bytes()), None)
+                .map(|result| result.map(|packet| converter.apply(packet))),
+            &mut output,
+        )
+        .unwrap();
+        normalize(&String::from_utf8(output).unwrap())
+    }
+
+    /// Collapses the padding between the fields of the output.
+    fn normalize(s: &str) -> String {
+        s.lines()
+            .map(|line| {
+                let (time, event) = line.split_once("] ").unwrap();
+                format!(
+                    "{}] {}\n",
+                    time,
+                    event.split_whitespace().collect::<Vec<_>>().join(" ")
+                )
+            })
+            .collect()
+    }
+
+    const PROTOCOL_A: &str = "\
+[       1.000000] /dev/input/event1: EV_ABS ABS_MT_POSITION_X 00000064
+[       1.000000] /dev/input/event1: EV_ABS ABS_MT_POSITION_Y 000000c8
+[       1.000000] /dev/input/event1: EV_SYN SYN_MT_REPORT 00000000
+[       1.000000] /dev/input/event1: EV_KEY BTN_TOUCH DOWN
+[       1.000000] /dev/input/event1: EV_SYN SYN_REPORT 00000000
+[       1.010000] /dev/input/event1: EV_ABS ABS_MT_POSITION_X 000001f4
+[       1.010000] /dev/input/event1: EV_ABS ABS_MT_POSITION_Y 000001f4
+[       1.010000] /dev/input/event1: EV_SYN SYN_MT_REPORT 00000000
+[       1.010000] /dev/input/event1: EV_ABS ABS_MT_POSITION_X 0000006e
+[       1.010000] /dev/input/event1: EV_ABS ABS_MT_POSITION_Y 000000c8
+[       1.010000] /dev/input/event1: EV_SYN SYN_MT_REPORT 00000000
+[       1.010000] /dev/input/event1: EV_SYN SYN_REPORT 00000000
+[       1.020000] /dev/input/event1: EV_ABS ABS_MT_POSITION_X 000001f4
+[       1.020000] /dev/input/event1: EV_ABS ABS_MT_POSITION_Y 000001fe
+[       1.020000] /dev/input/event1: EV_SYN SYN_MT_REPORT 00000000
+[       1.020000] /dev/input/event1: EV_SYN SYN_REPORT 00000000
+[       1.030000] /dev/input/event1: EV_SYN SYN_MT_REPORT 00000000
+[       1.030000] /dev/input/event1: EV_KEY BTN_TOUCH UP
+[       1.030000] /dev/input/event1: EV_SYN SYN_REPORT 00000000
+[       1.040000] /dev/input/event0: EV_KEY KEY_A DOWN
+[       1.040000] /dev/input/event0: EV_SYN SYN_REPORT 00000000
+";
+
+    const PROTOCOL_B: &str = "\
+[       1.000000] /dev/input/event1: EV_ABS ABS_MT_TRACKING_ID 00000000
+[       1.000000] /dev/input/event1: EV_ABS ABS_MT_POSITION_X 00000064
+[       1.000000] /dev/input/event1: EV_ABS ABS_MT_POSITION_Y 000000c8
+[       1.000000] /dev/input/event1: EV_KEY BTN_TOUCH DOWN
+[       1.000000] /dev/input/event1: EV_SYN SYN_REPORT 00000000
+[       1.010000] /dev/input/event1: EV_ABS ABS_MT_POSITION_X 0000006e
+[       1.010000] /dev/input/event1: EV_ABS ABS_MT_SLOT 00000001
+[       1.010000] /dev/input/event1: EV_ABS ABS_MT_TRACKING_ID 00000001
+[       1.010000] /dev/input/event1: EV_ABS ABS_MT_POSITION_X 000001f4
+[       1.010000] /dev/input/event1: EV_ABS ABS_MT_POSITION_Y 000001f4
+[       1.010000] /dev/input/event1: EV_SYN SYN_REPORT 00000000
+[       1.020000] /dev/input/event1: EV_ABS ABS_MT_SLOT 00000000
+[       1.020000] /dev/input/event1: EV_ABS ABS_MT_TRACKING_ID NONE
+[       1.020000] /dev/input/event1: EV_ABS ABS_MT_SLOT 00000001
+[       1.020000] /dev/input/event1: EV_ABS ABS_MT_POSITION_Y 000001fe
+[       1.020000] /dev/input/event1: EV_SYN SYN_REPORT 00000000
+[       1.030000] /dev/input/event1: EV_ABS ABS_MT_TRACKING_ID NONE
+[       1.030000] /dev/input/event1: EV_KEY BTN_TOUCH UP
+[       1.030000] /dev/input/event1: EV_SYN SYN_REPORT 00000000
+[       1.040000] /dev/input/event0: EV_KEY KEY_A DOWN
+[       1.040000] /dev/input/event0: EV_SYN SYN_REPORT 00000000
+";
+
+    #[test]
+    fn a_to_b() {
+        assert_eq!(convert(PROTOCOL_A, MtProtocol::B), PROTOCOL_B);
+        assert_eq!(convert(PROTOCOL_B, MtProtocol::B), PROTOCOL_B);
+    }
+
+    #[test]
+    fn b_to_a() {
+        let protocol_a = "\
+[       1.000000] /dev/input/event1: EV_ABS ABS_MT_POSITION_X 00000064
+[       1.000000] /dev/input/event1: EV_ABS ABS_MT_POSITION_Y 000000c8
+[       1.000000] /dev/input/event1: EV_ABS ABS_MT_TRACKING_ID 00000000
+[       1.000000] /dev/input/event1: EV_SYN SYN_MT_REPORT 00000000
+[       1.000000] /dev/input/event1: EV_KEY BTN_TOUCH DOWN
+[       1.000000] /dev/input/event1: EV_SYN SYN_REPORT 00000000
+[       1.010000] /dev/input/event1: EV_ABS ABS_MT_POSITION_X 0000006e
+[       1.010000] /dev/input/event1: EV_ABS ABS_MT_POSITION_Y 000000c8
+[       1.010000] /dev/input/event1: EV_ABS ABS_MT_TRACKING_ID 00000000
+[       1.010000] /dev/input/event1: EV_SYN SYN_MT_REPORT 00000000
+[       1.010000] /dev/input/event1: EV_ABS ABS_MT_POSITION_X 000001f4
+[       1.010000] /dev/input/event1: EV_ABS ABS_MT_POSITION_Y 000001f4
+[       1.010000] /dev/input/event1: EV_ABS ABS_MT_TRACKING_ID 00000001
+[       1.010000] /dev/input/event1: EV_SYN SYN_MT_REPORT 00000000
+[       1.010000] /dev/input/event1: EV_SYN SYN_REPORT 00000000
+[       1.020000] /dev/input/event1: EV_ABS ABS_MT_POSITION_X 000001f4
+[       1.020000] /dev/input/event1: EV_ABS ABS_MT_POSITION_Y 000001fe
+[       1.020000] /dev/input/event1: EV_ABS ABS_MT_TRACKING_ID 00000001
+[       1.020000] /dev/input/event1: EV_SYN SYN_MT_REPORT 00000000
+[       1.020000] /dev/input/event1: EV_SYN SYN_REPORT 00000000
+[       1.030000] /dev/input/event1: EV_SYN SYN_MT_REPORT 00000000
+[       1.030000] /dev/input/event1: EV_KEY BTN_TOUCH UP
+[       1.030000] /dev/input/event1: EV_SYN SYN_REPORT 00000000
+[       1.040000] /dev/input/event0: EV_KEY KEY_A DOWN
+[       1.040000] /dev/input/event0: EV_SYN SYN_REPORT 00000000
+";
+        assert_eq!(convert(PROTOCOL_B, MtProtocol::A), protocol_a);
+        assert_eq!(convert(PROTOCOL_A, MtProtocol::A), PROTOCOL_A);
+        // Tracking IDs of protocol A are kept.
+        assert_eq!(
+            convert(&convert(protocol_a, MtProtocol::B), MtProtocol::A),
+            protocol_a
+        );
+    }
+}
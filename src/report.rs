@@ -0,0 +1,135 @@
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// How late each packet was written relative to its scheduled time.
+#[derive(Debug, Default)]
+pub struct TimingReport {
+    lateness: Vec<Duration>,
+}
+
+impl TimingReport {
+    pub(crate) fn record(&mut self, lateness: Duration) {
+        self.lateness.push(lateness);
+    }
+
+    pub fn packets(&self) -> usize {
+        self.lateness.len()
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.lateness.is_empty() {
+            return Duration::ZERO;
+        }
+        let total: u128 = self.lateness.iter().map(Duration::as_nanos).sum();
+        Duration::from_nanos((total / self.lateness.len() as u128) as u64)
+    }
+
+    /// Nearest-rank percentile, `percent` in `0..=100`.
+    pub fn percentile(&self, percent: u32) -> Duration {
+        let mut sorted = self.lateness.clone();
+        sorted.sort_unstable();
+        let rank = (sorted.len() * percent as usize).div_ceil(100);
+        sorted
+            .get(rank.saturating_sub(1))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn max(&self) -> Duration {
+        self.lateness.iter().max().copied().unwrap_or_default()
+    }
+
+    /// Number of packets written more than `threshold` after their scheduled time.
+    pub fn late(&self, threshold: Duration) -> usize {
+        self.lateness
+            .iter()
+            .filter(|&&lateness| lateness > threshold)
+            .count()
+    }
+
+    pub fn write_text(&self, output: &mut impl Write, threshold: Duration) -> io::Result<()> {
+        fn ms(duration: Duration) -> String {
+            format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+        }
+
+        writeln!(output, "packets: {}", self.packets())?;
+        writeln!(
+            output,
+            "lateness: mean {}, p50 {}, p95 {}, p99 {}, max {}",
+            ms(self.mean()),
+            ms(self.percentile(50)),
+            ms(self.percentile(95)),
+            ms(self.percentile(99)),
+            ms(self.max())
+        )?;
+        writeln!(
+            output,
+            "late by more than {}: {}",
+            ms(threshold),
+            self.late(threshold)
+        )
+    }
+
+    pub fn write_json(&self, output: &mut impl Write, threshold: Duration) -> io::Result<()> {
+        writeln!(
+            output,
+            "{{\"packets\":{},\"mean_us\":{},\"p50_us\":{},\"p95_us\":{},\"p99_us\":{},\
+             \"max_us\":{},\"late_threshold_us\":{},\"late\":{}}}",
+            self.packets(),
+            self.mean().as_micros(),
+            self.percentile(50).as_micros(),
+            self.percentile(95).as_micros(),
+            self.percentile(99).as_micros(),
+            self.max().as_micros(),
+            threshold.as_micros(),
+            self.late(threshold)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TimingReport;
+    use std::time::Duration;
+
+    fn report() -> TimingReport {
+        let mut report = TimingReport::default();
+        for millis in (1..=100).rev() {
+            report.record(Duration::from_millis(millis));
+        }
+        report
+    }
+
+    #[test]
+    fn statistics() {
+        let report = report();
+        assert_eq!(report.packets(), 100);
+        assert_eq!(report.mean(), Duration::from_micros(50500));
+        assert_eq!(report.percentile(50), Duration::from_millis(50));
+        assert_eq!(report.percentile(95), Duration::from_millis(95));
+        assert_eq!(report.percentile(99), Duration::from_millis(99));
+        assert_eq!(report.max(), Duration::from_millis(100));
+        assert_eq!(report.late(Duration::from_millis(90)), 10);
+    }
+
+    #[test]
+    fn empty() {
+        let report = TimingReport::default();
+        assert_eq!(report.mean(), Duration::ZERO);
+        assert_eq!(report.percentile(99), Duration::ZERO);
+        assert_eq!(report.max(), Duration::ZERO);
+    }
+
+    #[test]
+    fn write_json() {
+        let mut output = vec![];
+        report()
+            .write_json(&mut output, Duration::from_millis(90))
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"packets\":100,\"mean_us\":50500,\"p50_us\":50000,\"p95_us\":95000,\
+             \"p99_us\":99000,\"max_us\":100000,\"late_threshold_us\":90000,\"late\":10}\n"
+        );
+    }
+}
@@ -0,0 +1,931 @@
+use std::collections::{hash_map::Entry, HashMap};
+use std::fs;
+use std::io::BufRead;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::device::DeviceInfo;
+use crate::expression;
+use crate::gesture::{Gesture, GestureGenerator, Point};
+use crate::keyboard::{Layout, Typist};
+use crate::linux::input_event_codes;
+use crate::{gen, packets, parse_all, parse_duration, Error, Packet, Radix, TimeVal};
+
+/// Most iterations of a `repeat` loop, so that a typo cannot compile a script forever.
+const MAX_REPEAT: u64 = 1_000_000;
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Argument(String),
+    /// `{`
+    Open,
+    /// `}`
+    Close,
+}
+
+#[derive(Debug)]
+enum Statement {
+    Command {
+        line: usize,
+        arguments: Vec<String>,
+    },
+    /// `repeat <COUNT> [VARIABLE] { ... }`
+    Repeat {
+        line: usize,
+        count: String,
+        variable: Option<String>,
+        body: Vec<Statement>,
+    },
+}
+
+#[derive(Debug)]
+struct RawPacket {
+    device: String,
+    time: Duration,
+    /// Type, code and value of each event.
+    events: Vec<(i32, i32, i32)>,
+}
+
+/// Compiles scripts of high-level commands like `tap 540 1200` or `type "hello"` into packets.
+///
+/// Commands run one after another on a common timeline, each starting when the previous one
+/// ends. Lines are split into whitespace separated arguments, where double quoted arguments may
+/// contain whitespace and the escapes `\"`, `\\`, `\n` and `\t`. Empty lines and everything from
+/// a `#` at the start of an argument are ignored.
+///
+/// `$NAME` and `${EXPRESSION}` in arguments are replaced by the values of variables and
+/// arithmetic expressions, and `$$` by `$`. Loops are expanded before the packets are written, so
+/// the same script always compiles to the same packets.
+#[derive(Debug)]
+pub struct Script {
+    /// Capabilities of devices that gestures are generated for. Other devices are queried when
+    /// the first gesture is generated for them.
+    pub device_info: HashMap<String, DeviceInfo>,
+    device: Option<String>,
+    layout: Layout,
+    rate: u32,
+    /// Radix of the numbers of `key` and `raw` commands that have no `0x` or `0d` prefix.
+    radix: Radix,
+    time: Duration,
+    variables: HashMap<String, f64>,
+    /// Times of the labels.
+    labels: HashMap<String, Duration>,
+    packets: Vec<Packet>,
+    /// `raw` events that have not been terminated by `SYN_REPORT` yet.
+    raw: Option<RawPacket>,
+    generators: HashMap<String, GestureGenerator>,
+    /// Files that are being read, to detect recursive includes.
+    files: Vec<PathBuf>,
+}
+
+impl Script {
+    /// Creates an empty script that writes to `device` until a `device` command selects another.
+    pub fn new(device: Option<&str>) -> Self {
+        Script {
+            device_info: HashMap::new(),
+            device: device.map(str::to_string),
+            layout: Layout::default(),
+            rate: 60,
+            radix: Radix::Decimal,
+            time: Duration::ZERO,
+            variables: HashMap::new(),
+            labels: HashMap::new(),
+            packets: vec![],
+            raw: None,
+            generators: HashMap::new(),
+            files: vec![],
+        }
+    }
+
+    /// Compiles the commands of `reader` and appends them to the script. Paths in `include`
+    /// commands are relative to `directory`.
+    pub fn read(
+        &mut self,
+        reader: &mut (impl BufRead + ?Sized),
+        directory: &Path,
+    ) -> Result<(), Error> {
+        let statements = parse(reader)?;
+        self.run(&statements, directory)
+    }
+
+    /// Compiles the commands of a file and appends them to the script. A file whose first line
+    /// that is not blank or a comment looks like a line of `getevent -l`, with or without `-t`,
+    /// is a capture instead, whose packets are appended, shifted to start at the current time.
+    pub fn read_file(&mut self, path: &Path) -> Result<(), Error> {
+        let canonical = path.canonicalize()?;
+        if self.files.contains(&canonical) {
+            return Err(Error::Format("recursive include".to_string()));
+        }
+        let content = fs::read_to_string(path)?;
+        self.files.push(canonical);
+        let result = if is_capture(&content) {
+            self.capture(&mut content.as_bytes())
+        } else {
+            self.read(
+                &mut content.as_bytes(),
+                path.parent().unwrap_or_else(|| Path::new("")),
+            )
+        };
+        self.files.pop();
+        result
+    }
+
+    pub(crate) fn finish(mut self) -> Vec<Packet> {
+        self.finish_raw();
+        self.packets
+    }
+
+    fn run(&mut self, statements: &[Statement], directory: &Path) -> Result<(), Error> {
+        for statement in statements {
+            match statement {
+                Statement::Command { line, arguments } => arguments
+                    .iter()
+                    .map(|argument| self.substitute(argument))
+                    .collect::<Result<Vec<_>, _>>()
+                    .and_then(|arguments| self.command(&arguments, directory))
+                    .map_err(|error| Error::Line(*line, Box::new(error)))?,
+                Statement::Repeat {
+                    line,
+                    count,
+                    variable,
+                    body,
+                } => {
+                    let count =
+                        self.substitute(count)
+                            .and_then(|count| match count.parse::<u64>() {
+                                Ok(count) if count <= MAX_REPEAT => Ok(count),
+                                Ok(_) => Err(Error::Format(format!(
+                                    "repeat count must be at most {}, not {}",
+                                    MAX_REPEAT, count
+                                ))),
+                                Err(_) => Err(Error::Format(format!(
+                                    "repeat count must be a non-negative integer, not {:?}",
+                                    count
+                                ))),
+                            });
+                    let count = count.map_err(|error| Error::Line(*line, Box::new(error)))?;
+                    for index in 0..count {
+                        if let Some(variable) = variable {
+                            self.variables.insert(variable.clone(), index as f64);
+                        }
+                        self.run(body, directory)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn evaluate(&self, expression: &str) -> Result<f64, Error> {
+        expression::evaluate(expression, &|name| self.variables.get(name).copied())
+            .map_err(Error::Format)
+    }
+
+    /// Replaces variables and expressions in an argument by their values.
+    fn substitute(&self, argument: &str) -> Result<String, Error> {
+        let mut result = String::new();
+        let mut chars = argument.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                result.push(c);
+                continue;
+            }
+            match chars.peek() {
+                Some('$') => {
+                    chars.next();
+                    result.push('$');
+                }
+                Some('{') => {
+                    chars.next();
+                    let mut expression = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => expression.push(c),
+                            None => {
+                                return Err(Error::Format("missing `}` after `${`".to_string()))
+                            }
+                        }
+                    }
+                    result.push_str(&expression::format(self.evaluate(&expression)?));
+                }
+                Some(&c) if c.is_alphabetic() || c == '_' => {
+                    let mut name = String::new();
+                    while let Some(c) = chars.next_if(|&c| c.is_alphanumeric() || c == '_') {
+                        name.push(c);
+                    }
+                    result.push_str(&expression::format(self.evaluate(&name)?));
+                }
+                _ => result.push('$'),
+            }
+        }
+        Ok(result)
+    }
+
+    fn command(&mut self, arguments: &[String], directory: &Path) -> Result<(), Error> {
+        let (command, arguments) = match arguments.split_first() {
+            Some((command, arguments)) => (command.as_str(), arguments),
+            None => return Ok(()),
+        };
+        let arity = |min: usize, max: usize| {
+            if (min..=max).contains(&arguments.len()) {
+                Ok(())
+            } else if min == max {
+                Err(Error::Format(format!(
+                    "`{}` takes {} arguments, not {}",
+                    command,
+                    min,
+                    arguments.len()
+                )))
+            } else {
+                Err(Error::Format(format!(
+                    "`{}` takes {} to {} arguments, not {}",
+                    command,
+                    min,
+                    max,
+                    arguments.len()
+                )))
+            }
+        };
+        let number = |index: usize| number(&arguments[index]);
+        let point = |index: usize| -> Result<Point, Error> {
+            Ok(Point {
+                x: number(index)?,
+                y: number(index + 1)?,
+            })
+        };
+        // Optional duration after the required arguments.
+        let duration_or = |index: usize, millis| match arguments.get(index) {
+            Some(argument) => duration(argument),
+            None => Ok(Duration::from_millis(millis)),
+        };
+
+        if command != "raw" {
+            self.finish_raw();
+        }
+        match command {
+            "device" => {
+                arity(1, 1)?;
+                self.device = Some(arguments[0].clone());
+            }
+            "layout" => {
+                arity(1, 1)?;
+                self.layout = arguments[0].parse().map_err(Error::Format)?;
+            }
+            "rate" => {
+                arity(1, 1)?;
+                self.rate = arguments[0].parse().map_err(|error| {
+                    Error::ParseInt(error, format!("parsing rate {:?}", arguments[0]))
+                })?;
+                if !(1..=1_000_000).contains(&self.rate) {
+                    return Err(Error::Format(format!(
+                        "expected a rate from 1 to 1000000, not {}",
+                        self.rate
+                    )));
+                }
+            }
+            "radix" => {
+                arity(1, 1)?;
+                self.radix = arguments[0].parse().map_err(Error::Format)?;
+            }
+            "sleep" => {
+                arity(1, 1)?;
+                self.time += duration(&arguments[0])?;
+            }
+            "let" => {
+                if arguments.len() < 3 || arguments[1] != "=" {
+                    return Err(Error::Format(
+                        "expected `let <NAME> = <EXPRESSION>`".to_string(),
+                    ));
+                }
+                let name = &arguments[0];
+                if !is_identifier(name) {
+                    return Err(Error::Format(format!("invalid variable name {:?}", name)));
+                }
+                let value = self.evaluate(&arguments[2..].join(" "))?;
+                self.variables.insert(name.clone(), value);
+            }
+            "label" => {
+                arity(1, 1)?;
+                self.labels.insert(arguments[0].clone(), self.time);
+            }
+            "wait" => {
+                arity(1, 2)?;
+                let (start, offset) = match arguments {
+                    [offset] => (Duration::ZERO, offset),
+                    [label, offset] => (
+                        *self
+                            .labels
+                            .get(label)
+                            .ok_or_else(|| Error::Format(format!("unknown label {:?}", label)))?,
+                        offset,
+                    ),
+                    _ => unreachable!(),
+                };
+                self.time = self.time.max(start + duration(offset)?);
+            }
+            "tap" => {
+                arity(2, 3)?;
+                self.gesture(Gesture::tap(point(0)?, duration_or(2, 100)?))?;
+            }
+            "double-tap" => {
+                arity(2, 3)?;
+                let duration = duration_or(2, 100)?;
+                self.gesture(Gesture::double_tap(point(0)?, duration, duration))?;
+            }
+            "long-press" => {
+                arity(2, 3)?;
+                self.gesture(Gesture::long_press(point(0)?, duration_or(2, 1000)?))?;
+            }
+            "swipe" => {
+                arity(4, 5)?;
+                self.gesture(Gesture::swipe(point(0)?, point(2)?, duration_or(4, 300)?))?;
+            }
+            "pinch" => {
+                arity(4, 5)?;
+                let gesture =
+                    Gesture::pinch(point(0)?, number(2)?, number(3)?, 0.0, duration_or(4, 500)?);
+                self.gesture(gesture)?;
+            }
+            "rotate" => {
+                arity(5, 6)?;
+                let gesture = Gesture::rotate(
+                    point(0)?,
+                    number(2)?,
+                    number(3)?,
+                    number(4)?,
+                    duration_or(5, 500)?,
+                );
+                self.gesture(gesture)?;
+            }
+            "key" => {
+                arity(1, 2)?;
+                let r#type = input_event_codes::EV_KEY as u16;
+                let code =
+                    gen::get_code_value(r#type, &arguments[0], self.radix).map_err(|error| {
+                        Error::Parse(error, format!("parsing key {:?}", arguments[0]))
+                    })?;
+                let duration = duration_or(1, 100)?;
+                let device = self.device()?;
+                let key = |time, value| {
+                    Packet::new(
+                        &device,
+                        time,
+                        [(input_event_codes::EV_KEY, i32::from(code), value)],
+                    )
+                };
+                self.append(vec![key(Duration::ZERO, 1), key(duration, 0)]);
+            }
+            "type" => {
+                arity(1, 2)?;
+                let mut typist = Typist::new(&self.device()?, self.layout);
+                typist.delay = duration_or(1, 100)?;
+                let packets = typist.generate(&arguments[0])?;
+                self.append(packets);
+            }
+            "raw" => {
+                arity(3, 3)?;
+                self.raw(&arguments[0], &arguments[1], &arguments[2])?;
+            }
+            "include" => {
+                arity(1, 1)?;
+                let path = directory.join(&arguments[0]);
+                self.read_file(&path)
+                    .map_err(|error| Error::Include(arguments[0].clone(), Box::new(error)))?;
+            }
+            _ => return Err(Error::Format(format!("unknown command {:?}", command))),
+        }
+        Ok(())
+    }
+
+    fn device(&self) -> Result<String, Error> {
+        self.device.clone().ok_or_else(|| {
+            Error::Format("no device selected, use `device <DEVICE>` first".to_string())
+        })
+    }
+
+    fn gesture(&mut self, gesture: Gesture) -> Result<(), Error> {
+        let device = self.device()?;
+        let generator = match self.generators.entry(device.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let info = match self.device_info.get(&device) {
+                    Some(info) => info.clone(),
+                    None => DeviceInfo::query(&device)?,
+                };
+                entry.insert(GestureGenerator::new(&device, info))
+            }
+        };
+        generator.rate = self.rate;
+        let packets = generator.generate(&gesture)?;
+        self.append(packets);
+        Ok(())
+    }
+
+    /// Appends packets timestamped relative to the current time and advances the current time
+    /// to the last of them.
+    fn append(&mut self, packets: Vec<Packet>) {
+        let mut end = Duration::ZERO;
+        for mut packet in packets {
+            end = end.max(packet.time());
+            for event in &mut packet.events {
+                event.time = TimeVal::from_duration(event.time.to_duration() + self.time);
+            }
+            self.packets.push(packet);
+        }
+        self.time += end;
+    }
+
+    /// Appends the packets of a getevent capture, shifted to start at the current time.
+    fn capture(&mut self, reader: &mut impl BufRead) -> Result<(), Error> {
+        let mut packets =
+            packets(parse_all(reader), self.device.as_deref()).collect::<Result<Vec<_>, _>>()?;
+        let start = packets.iter().map(Packet::time).min().unwrap_or_default();
+        for packet in &mut packets {
+            for event in &mut packet.events {
+                event.time = TimeVal::from_duration(event.time.to_duration().saturating_sub(start));
+            }
+        }
+        self.append(packets);
+        Ok(())
+    }
+
+    fn raw(&mut self, r#type: &str, code: &str, value: &str) -> Result<(), Error> {
+        let r#type = gen::get_type_value(r#type, self.radix)
+            .map_err(|error| Error::Parse(error, format!("parsing type {:?}", r#type)))?;
+        let code = gen::get_code_value(r#type, code, self.radix)
+            .map_err(|error| Error::Parse(error, format!("parsing code {:?}", code)))?;
+        let value = gen::get_value_value(r#type, code, value, self.radix)
+            .map_err(|error| Error::Parse(error, format!("parsing value {:?}", value)))?;
+        let (r#type, code) = (i32::from(r#type), i32::from(code));
+        if self.raw.is_none() {
+            self.raw = Some(RawPacket {
+                device: self.device()?,
+                time: self.time,
+                events: vec![],
+            });
+        }
+        if r#type == input_event_codes::EV_SYN && code == input_event_codes::SYN_REPORT {
+            self.finish_raw();
+        } else {
+            self.raw
+                .as_mut()
+                .unwrap()
+                .events
+                .push((r#type, code, value));
+        }
+        Ok(())
+    }
+
+    /// Appends the pending packet of `raw` events, terminated by `SYN_REPORT`.
+    fn finish_raw(&mut self) {
+        if let Some(raw) = self.raw.take() {
+            self.packets
+                .push(Packet::new(&raw.device, raw.time, raw.events));
+        }
+    }
+}
+
+/// Whether the first line that is not blank or a comment starts with a timestamp like
+/// `[     100.000000]` or a device like `/dev/input/event1:`.
+fn is_capture(content: &str) -> bool {
+    let line = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'));
+    match line.and_then(|line| line.split_whitespace().next()) {
+        Some(field) => field.starts_with('[') || (field.starts_with('/') && field.ends_with(':')),
+        None => false,
+    }
+}
+
+/// Parses the lines of a script into statements.
+fn parse(reader: &mut (impl BufRead + ?Sized)) -> Result<Vec<Statement>, Error> {
+    let mut statements = vec![];
+    // Line and arguments of the `repeat` of each open block, and the statements before it.
+    let mut blocks: Vec<(usize, Vec<String>, Vec<Statement>)> = vec![];
+    for (index, text) in reader.lines().enumerate() {
+        let text = text?;
+        let line = index + 1;
+        let error = |message: &str| Error::Line(line, Box::new(Error::Format(message.to_string())));
+        let tokens = split(&text).map_err(|error| Error::Line(line, Box::new(error)))?;
+        let mut arguments = vec![];
+        for token in tokens {
+            match token {
+                Token::Argument(argument) => arguments.push(argument),
+                Token::Open => {
+                    if arguments.first().map(String::as_str) != Some("repeat") {
+                        return Err(error("`{` must follow `repeat <COUNT> [VARIABLE]`"));
+                    }
+                    blocks.push((line, mem::take(&mut arguments), mem::take(&mut statements)));
+                }
+                Token::Close => {
+                    if !arguments.is_empty() {
+                        statements.push(Statement::Command {
+                            line,
+                            arguments: mem::take(&mut arguments),
+                        });
+                    }
+                    let (line, header, outer) =
+                        blocks.pop().ok_or_else(|| error("unexpected `}`"))?;
+                    let body = mem::replace(&mut statements, outer);
+                    statements.push(repeat(line, header, body)?);
+                }
+            }
+        }
+        if !arguments.is_empty() {
+            statements.push(Statement::Command { line, arguments });
+        }
+    }
+    if let Some((line, _, _)) = blocks.last() {
+        return Err(Error::Line(
+            *line,
+            Box::new(Error::Format("missing `}`".to_string())),
+        ));
+    }
+    Ok(statements)
+}
+
+fn repeat(line: usize, mut header: Vec<String>, body: Vec<Statement>) -> Result<Statement, Error> {
+    let variable = match header.len() {
+        2 => None,
+        3 if is_identifier(&header[2]) => header.pop(),
+        _ => {
+            return Err(Error::Line(
+                line,
+                Box::new(Error::Format(
+                    "expected `repeat <COUNT> [VARIABLE] {`".to_string(),
+                )),
+            ))
+        }
+    };
+    Ok(Statement::Repeat {
+        line,
+        count: header.pop().unwrap(),
+        variable,
+        body,
+    })
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Splits a line into arguments and unquoted `{` and `}`.
+fn split(line: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = vec![];
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut argument = String::new();
+        match chars.peek() {
+            None | Some('#') => break,
+            Some('"') => {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => argument.push(match chars.next() {
+                            Some('n') => '\n',
+                            Some('t') => '\t',
+                            Some(c @ ('"' | '\\')) => c,
+                            c => {
+                                return Err(Error::Format(format!(
+                                    "unknown escape `\\{}`",
+                                    c.map(String::from).unwrap_or_default()
+                                )))
+                            }
+                        }),
+                        Some(c) => argument.push(c),
+                        None => return Err(Error::Format("missing closing `\"`".to_string())),
+                    }
+                }
+            }
+            Some(_) => {
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    argument.push(c);
+                    // Expressions may contain whitespace.
+                    if c == '$' && chars.next_if_eq(&'{').is_some() {
+                        argument.push('{');
+                        for c in chars.by_ref() {
+                            argument.push(c);
+                            if c == '}' {
+                                break;
+                            }
+                        }
+                    }
+                }
+                match argument.as_str() {
+                    "{" => {
+                        tokens.push(Token::Open);
+                        continue;
+                    }
+                    "}" => {
+                        tokens.push(Token::Close);
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        tokens.push(Token::Argument(argument));
+    }
+    Ok(tokens)
+}
+
+fn number(s: &str) -> Result<f64, Error> {
+    s.parse()
+        .map_err(|_| Error::Format(format!("invalid number {:?}", s)))
+}
+
+fn duration(s: &str) -> Result<Duration, Error> {
+    parse_duration(s).map_err(Error::Format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split, Script, Token};
+    use crate::device::DeviceInfo;
+    use crate::linux::input_event_codes;
+    use std::fs;
+    use std::path::Path;
+
+    const DUMP: &str = "\
+add device 2: /dev/input/event2
+  events:
+    ABS (0003): ABS_MT_SLOT           : value 0, min 0, max 9, fuzz 0, flat 0, resolution 0
+                ABS_MT_POSITION_X     : value 0, min 0, max 1079, fuzz 0, flat 0, resolution 0
+                ABS_MT_POSITION_Y     : value 0, min 0, max 2339, fuzz 0, flat 0, resolution 0
+                ABS_MT_TRACKING_ID    : value 0, min 0, max 65535, fuzz 0, flat 0, resolution 0
+";
+
+    /// Packets as the offset in milliseconds, the device and the number of events.
+    fn compile(input: &str) -> Vec<(u128, String, usize)> {
+        let mut script = Script::new(Some("/dev/input/event2"));
+        script.device_info = DeviceInfo::all_from_getevent(&mut DUMP.as_bytes())
+            .unwrap()
+            .into_iter()
+            .collect();
+        script.read(&mut input.as_bytes(), Path::new("")).unwrap();
+        script
+            .finish()
+            .iter()
+            .map(|packet| {
+                (
+                    packet.time().as_millis(),
+                    packet.device.clone(),
+                    packet.events.len(),
+                )
+            })
+            .collect()
+    }
+
+    fn arguments(line: &str) -> Vec<String> {
+        split(line)
+            .unwrap()
+            .into_iter()
+            .map(|token| match token {
+                Token::Argument(argument) => argument,
+                Token::Open => "{".to_string(),
+                Token::Close => "}".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_arguments() {
+        assert_eq!(arguments("  tap 540\t1200  "), ["tap", "540", "1200"]);
+        assert_eq!(
+            arguments(r#"type "say \"hi\"\n" # comment"#),
+            ["type", "say \"hi\"\n"]
+        );
+        assert_eq!(arguments("# comment"), Vec::<String>::new());
+        assert_eq!(arguments(r#"type """#), ["type", ""]);
+        assert_eq!(
+            split(r#"repeat 2 { type "}" }"#).unwrap(),
+            [
+                Token::Argument("repeat".to_string()),
+                Token::Argument("2".to_string()),
+                Token::Open,
+                Token::Argument("type".to_string()),
+                Token::Argument("}".to_string()),
+                Token::Close,
+            ]
+        );
+        assert_eq!(arguments("tap ${x + 1} $y"), ["tap", "${x + 1}", "$y"]);
+        assert!(split(r#"type "hi"#).is_err());
+        assert!(split(r#"type "\q""#).is_err());
+    }
+
+    #[test]
+    fn commands() {
+        let touch = "/dev/input/event2".to_string();
+        let keys = "/dev/input/event0".to_string();
+        let input = "\
+tap 540 1200
+
+sleep 2s
+device /dev/input/event0
+key KEY_HOME 50ms
+type \"aB\" 20ms
+raw EV_KEY KEY_POWER DOWN
+raw EV_SYN SYN_REPORT 0
+raw EV_KEY KEY_POWER UP
+";
+        assert_eq!(
+            compile(input),
+            [
+                (0, touch.clone(), 5),
+                (100, touch, 2),
+                (2100, keys.clone(), 2),
+                (2150, keys.clone(), 2),
+                (2150, keys.clone(), 2),
+                (2160, keys.clone(), 2),
+                (2170, keys.clone(), 3),
+                (2180, keys.clone(), 3),
+                (2180, keys.clone(), 2),
+                (2180, keys, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn control_flow() {
+        let keys = "/dev/input/event0".to_string();
+        let input = "\
+device /dev/input/event0
+let n = 2
+repeat $n i {
+    key KEY_A ${10 * (i + 1)}ms
+}
+repeat 0 { key KEY_B }
+label start
+let half = n / 4
+sleep ${half * 10}ms
+wait start 20ms
+key KEY_C
+wait 10ms
+repeat 1 { repeat 1 { key KEY_D 5ms } }
+";
+        let times = compile(input)
+            .into_iter()
+            .map(|(time, device, _)| {
+                assert_eq!(device, keys);
+                time
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(times, [0, 10, 10, 30, 50, 150, 150, 155]);
+    }
+
+    #[test]
+    fn radix() {
+        let input = "\
+raw EV_ABS ABS_MT_POSITION_X 540
+raw EV_ABS ABS_MT_POSITION_Y 0x540
+radix hex
+raw EV_ABS ABS_MT_POSITION_X 540
+raw EV_ABS ABS_MT_POSITION_Y 0d540
+raw EV_ABS ABS_MT_TRACKING_ID ffffffff
+";
+        let mut script = Script::new(Some("/dev/input/event2"));
+        script.read(&mut input.as_bytes(), Path::new("")).unwrap();
+        let values = script
+            .finish()
+            .iter()
+            .flat_map(|packet| packet.events.iter().map(|event| event.value))
+            .collect::<Vec<_>>();
+        assert_eq!(values, [540, 0x540, 0, 0x540, 540, -1, 0]);
+    }
+
+    #[test]
+    fn errors() {
+        let error = |input: &str| {
+            Script::new(None)
+                .read(&mut input.as_bytes(), Path::new(""))
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            error("\nkey KEY_HOME"),
+            "line 2: no device selected, use `device <DEVICE>` first"
+        );
+        assert_eq!(
+            error("tap 1"),
+            "line 1: `tap` takes 2 to 3 arguments, not 1"
+        );
+        assert_eq!(error("sleep"), "line 1: `sleep` takes 1 arguments, not 0");
+        assert_eq!(
+            error("sleep 1h"),
+            "line 1: unknown unit \"h\" in duration \"1h\""
+        );
+        assert_eq!(error("device d\ntap x 1"), "line 2: invalid number \"x\"");
+        assert_eq!(error("jump"), "line 1: unknown command \"jump\"");
+        assert_eq!(error("\nrepeat 2 {\nsleep 1s\n"), "line 2: missing `}`");
+        assert_eq!(error("repeat 2 { }\n}"), "line 2: unexpected `}`");
+        assert_eq!(
+            error("sleep 1s {"),
+            "line 1: `{` must follow `repeat <COUNT> [VARIABLE]`"
+        );
+        assert_eq!(
+            error("repeat -1 {\n}"),
+            "line 1: repeat count must be a non-negative integer, not \"-1\""
+        );
+        assert_eq!(
+            error("repeat 1000001 {\n}"),
+            "line 1: repeat count must be at most 1000000, not 1000001"
+        );
+        assert_eq!(
+            error("repeat 1 {\nsleep ${x}s\n}"),
+            "line 2: unknown variable \"x\""
+        );
+        assert_eq!(error("let x = 1 +"), "line 1: missing operand");
+        assert_eq!(
+            error("type $$"),
+            "line 1: no device selected, use `device <DEVICE>` first"
+        );
+        assert_eq!(error("wait start 1s"), "line 1: unknown label \"start\"");
+        assert_eq!(
+            error("radix octal"),
+            "line 1: unknown radix \"octal\", expected \"hex\" or \"decimal\""
+        );
+    }
+
+    #[test]
+    fn include() {
+        let directory =
+            std::env::temp_dir().join(format!("sendevent-script-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("main.evs"), "key KEY_A\ninclude other.evs\n").unwrap();
+        fs::write(directory.join("other.evs"), "sleep 1s\nkey KEY_B\n").unwrap();
+        fs::write(directory.join("loop.evs"), "\ninclude loop.evs\n").unwrap();
+        fs::write(
+            directory.join("unlock.getevent"),
+            "\
+[     100.000000] /dev/input/event1: EV_KEY KEY_POWER DOWN
+[     100.000000] /dev/input/event1: EV_SYN SYN_REPORT 00000000
+[     100.250000] /dev/input/event1: EV_KEY KEY_POWER UP
+[     100.250000] /dev/input/event1: EV_SYN SYN_REPORT 00000000
+",
+        )
+        .unwrap();
+        fs::write(
+            directory.join("unlock.evs"),
+            "repeat 2 {\n  include unlock.getevent\n  sleep 1s\n}\n",
+        )
+        .unwrap();
+        // Captures may start with comments and lack timestamps.
+        fs::write(
+            directory.join("commented.getevent"),
+            "\n# Unlock\n[     100.000000] /dev/input/event1: EV_KEY KEY_POWER DOWN\n",
+        )
+        .unwrap();
+        fs::write(
+            directory.join("untimed.getevent"),
+            "/dev/input/event1: EV_KEY KEY_POWER DOWN\n/dev/input/event1: EV_SYN SYN_REPORT 0\n",
+        )
+        .unwrap();
+
+        let mut script = Script::new(Some("/dev/input/event0"));
+        script.read_file(&directory.join("main.evs")).unwrap();
+        let packets = script.finish();
+        assert_eq!(packets.len(), 4);
+        assert_eq!(packets[2].time().as_millis(), 1100);
+        assert_eq!(
+            i32::from(packets[2].events[0].code),
+            input_event_codes::KEY_B
+        );
+
+        let mut script = Script::new(None);
+        script.read_file(&directory.join("unlock.evs")).unwrap();
+        let packets = script.finish();
+        assert_eq!(
+            packets
+                .iter()
+                .map(|packet| (packet.time().as_millis(), packet.device.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                (0, "/dev/input/event1"),
+                (250, "/dev/input/event1"),
+                (1250, "/dev/input/event1"),
+                (1500, "/dev/input/event1"),
+            ]
+        );
+
+        for name in ["commented.getevent", "untimed.getevent"] {
+            let mut script = Script::new(None);
+            script.read_file(&directory.join(name)).unwrap();
+            let packets = script.finish();
+            assert_eq!(packets.len(), 1);
+            assert_eq!(packets[0].device, "/dev/input/event1");
+        }
+
+        let error = Script::new(None)
+            .read_file(&directory.join("loop.evs"))
+            .unwrap_err();
+        assert_eq!(error.to_string(), "line 2: loop.evs: recursive include");
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
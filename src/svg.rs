@@ -0,0 +1,290 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::time::Duration;
+
+use crate::device::DeviceInfo;
+use crate::linux::input_event_codes::*;
+use crate::mt::MtState;
+use crate::{Error, Packet};
+
+/// Path of a contact from touch down to lift off.
+#[derive(Debug)]
+struct Trace {
+    tracking_id: i32,
+    points: Vec<(Duration, i32, i32)>,
+    /// Time of the lift off, unless the contact still touches at the end.
+    end: Option<Duration>,
+}
+
+/// Contacts of one device.
+#[derive(Debug, Default)]
+struct DeviceState {
+    mt: MtState,
+    /// Index of the trace of the contact in each slot.
+    open: BTreeMap<i32, usize>,
+    traces: Vec<Trace>,
+}
+
+impl DeviceState {
+    fn packet(&mut self, packet: &Packet, time: Duration) {
+        self.mt.packet(packet);
+        let contacts = self.mt.contacts();
+        let traces = &mut self.traces;
+        self.open.retain(|slot, &mut index| {
+            let trace = &mut traces[index];
+            match contacts.iter().find(|touch| touch.slot == *slot) {
+                Some(touch) if touch.tracking_id == trace.tracking_id => true,
+                _ => {
+                    trace.end = Some(time);
+                    false
+                }
+            }
+        });
+        for touch in contacts {
+            match self.open.get(&touch.slot) {
+                Some(&index) => {
+                    let trace = &mut self.traces[index];
+                    let &(_, x, y) = trace.points.last().unwrap();
+                    if (x, y) != (touch.x, touch.y) {
+                        trace.points.push((time, touch.x, touch.y));
+                    }
+                }
+                None => {
+                    self.open.insert(touch.slot, self.traces.len());
+                    self.traces.push(Trace {
+                        tracking_id: touch.tracking_id,
+                        points: vec![(time, touch.x, touch.y)],
+                        end: None,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Range of an axis, from the device if it reports it and from zero to the largest position
+/// otherwise.
+fn range(
+    info: Option<&DeviceInfo>,
+    codes: [i32; 2],
+    positions: impl Iterator<Item = i32>,
+) -> (i32, i32) {
+    if let Some(absinfo) = info.and_then(|info| codes.iter().find_map(|&code| info.abs(code))) {
+        return (absinfo.minimum, absinfo.maximum);
+    }
+    positions.fold((0, 0), |(min, max), position| {
+        (min.min(position), max.max(position))
+    })
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{}.{:03}s", duration.as_secs(), duration.subsec_millis())
+}
+
+/// Renders the path of each contact as a polyline in an SVG image that is `width` pixels wide
+/// and covers the range of the axes of the device. Contacts are coloured by tracking ID and
+/// marked with a circle where they touched and a square where they lifted, labelled with the
+/// times relative to the first packet.
+pub(crate) fn render(
+    packets: impl Iterator<Item = Result<Packet, Error>>,
+    device_info: &BTreeMap<String, DeviceInfo>,
+    width: u32,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    let mut devices: HashMap<String, DeviceState> = HashMap::new();
+    let mut start = None;
+    for result in packets {
+        let packet = result?;
+        let start = *start.get_or_insert(packet.time());
+        devices
+            .entry(packet.device.clone())
+            .or_default()
+            .packet(&packet, packet.time().saturating_sub(start));
+    }
+    let mut devices = devices
+        .into_iter()
+        .filter(|(_, state)| !state.traces.is_empty());
+    let (device, state) = devices
+        .next()
+        .ok_or_else(|| Error::Format("no touch contacts in the input".to_string()))?;
+    if let Some((other, _)) = devices.next() {
+        let mut names = [device, other];
+        names.sort();
+        return Err(Error::Format(format!(
+            "contacts on {} and {}, select one device with `--include device=<DEVICE>`",
+            names[0], names[1]
+        )));
+    }
+
+    let info = device_info.get(&device);
+    let points = || state.traces.iter().flat_map(|trace| &trace.points);
+    let x = range(info, [ABS_MT_POSITION_X, ABS_X], points().map(|p| p.1));
+    let y = range(info, [ABS_MT_POSITION_Y, ABS_Y], points().map(|p| p.2));
+    let size = (x.1 - x.0 + 1, y.1 - y.0 + 1);
+    let height = (f64::from(width) * f64::from(size.1) / f64::from(size.0)).round();
+    // Size of the markers and labels in device units.
+    let unit = f64::from(size.0.max(size.1)) / 100.0;
+
+    writeln!(
+        output,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+         viewBox=\"{} {} {} {}\">",
+        width, height, x.0, y.0, size.0, size.1
+    )?;
+    writeln!(
+        output,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"white\" stroke=\"gray\" \
+         stroke-width=\"{}\"/>",
+        x.0,
+        y.0,
+        size.0,
+        size.1,
+        unit / 5.0
+    )?;
+    for trace in &state.traces {
+        let (first, last) = (trace.points[0], *trace.points.last().unwrap());
+        let end = trace.end.unwrap_or(last.0);
+        // Golden angle steps keep the hues of consecutive tracking IDs apart.
+        let hue = (f64::from(trace.tracking_id) * 137.508)
+            .rem_euclid(360.0)
+            .round();
+        writeln!(
+            output,
+            "<g stroke=\"hsl({hue}, 80%, 40%)\" fill=\"hsl({hue}, 80%, 40%)\" \
+             font-size=\"{}\" font-family=\"sans-serif\">",
+            unit * 2.0
+        )?;
+        writeln!(
+            output,
+            "<title>{}: tracking ID {}, {} to {}</title>",
+            device,
+            trace.tracking_id,
+            seconds(first.0),
+            seconds(end)
+        )?;
+        let points: Vec<_> = trace
+            .points
+            .iter()
+            .map(|(_, x, y)| format!("{},{}", x, y))
+            .collect();
+        writeln!(
+            output,
+            "<polyline points=\"{}\" fill=\"none\" stroke-width=\"{}\"/>",
+            points.join(" "),
+            unit / 2.0
+        )?;
+        writeln!(
+            output,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\"/>",
+            first.1, first.2, unit
+        )?;
+        writeln!(
+            output,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke-width=\"{}\"/>",
+            f64::from(last.1) - unit,
+            f64::from(last.2) - unit,
+            unit * 2.0,
+            unit * 2.0,
+            unit / 4.0
+        )?;
+        let labels = if trace.points.len() == 1 {
+            vec![(first, format!("{} to {}", seconds(first.0), seconds(end)))]
+        } else {
+            vec![(first, seconds(first.0)), (last, seconds(end))]
+        };
+        for ((_, x, y), label) in labels {
+            writeln!(
+                output,
+                "<text x=\"{}\" y=\"{}\" stroke=\"none\">{}</text>",
+                f64::from(x) + unit * 1.5,
+                y,
+                label
+            )?;
+        }
+        writeln!(output, "</g>")?;
+    }
+    writeln!(output, "</svg>")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::device::DeviceInfo;
+    use crate::{packets, parse_all};
+    use std::collections::BTreeMap;
+
+    const DUMP: &str = "\
+add device 2: /dev/input/event2
+  events:
+    ABS (0003): ABS_MT_POSITION_X     : value 0, min 0, max 999, fuzz 0, flat 0, resolution 0
+                ABS_MT_POSITION_Y     : value 0, min 0, max 1999, fuzz 0, flat 0, resolution 0
+";
+
+    const INPUT: &str = "\
+[       1.000000] /dev/input/event2: EV_ABS ABS_MT_SLOT 00000000
+[       1.000000] /dev/input/event2: EV_ABS ABS_MT_TRACKING_ID 00000007
+[       1.000000] /dev/input/event2: EV_ABS ABS_MT_POSITION_X 00000064
+[       1.000000] /dev/input/event2: EV_ABS ABS_MT_POSITION_Y 000000c8
+[       1.000000] /dev/input/event2: EV_SYN SYN_REPORT 00000000
+[       1.250000] /dev/input/event2: EV_ABS ABS_MT_POSITION_Y 0000012c
+[       1.250000] /dev/input/event2: EV_SYN SYN_REPORT 00000000
+[       1.500000] /dev/input/event2: EV_ABS ABS_MT_TRACKING_ID ffffffff
+[       1.500000] /dev/input/event2: EV_SYN SYN_REPORT 00000000
+[       1.500000] /dev/input/event0: EV_KEY KEY_POWER DOWN
+[       1.500000] /dev/input/event0: EV_SYN SYN_REPORT 00000000
+";
+
+    fn svg(input: &str, device_info: &BTreeMap<String, DeviceInfo>) -> Result<String, String> {
+        let mut output = vec![];
+        render(
+            packets(parse_all(&mut input.as_bytes()), None),
+            device_info,
+            500,
+            &mut output,
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn render_contacts() {
+        let device_info = DeviceInfo::all_from_getevent(&mut DUMP.as_bytes()).unwrap();
+        assert_eq!(
+            svg(INPUT, &device_info).unwrap(),
+            "\
+<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"500\" height=\"1000\" viewBox=\"0 0 1000 2000\">
+<rect x=\"0\" y=\"0\" width=\"1000\" height=\"2000\" fill=\"white\" stroke=\"gray\" stroke-width=\"4\"/>
+<g stroke=\"hsl(243, 80%, 40%)\" fill=\"hsl(243, 80%, 40%)\" font-size=\"40\" font-family=\"sans-serif\">
+<title>/dev/input/event2: tracking ID 7, 0.000s to 0.500s</title>
+<polyline points=\"100,200 100,300\" fill=\"none\" stroke-width=\"10\"/>
+<circle cx=\"100\" cy=\"200\" r=\"20\"/>
+<rect x=\"80\" y=\"280\" width=\"40\" height=\"40\" fill=\"none\" stroke-width=\"5\"/>
+<text x=\"130\" y=\"200\" stroke=\"none\">0.000s</text>
+<text x=\"130\" y=\"300\" stroke=\"none\">0.500s</text>
+</g>
+</svg>
+"
+        );
+
+        // Without the ranges of the device, the image reaches up to the largest positions.
+        let output = svg(INPUT, &BTreeMap::new()).unwrap();
+        assert!(output.contains("width=\"500\" height=\"1490\" viewBox=\"0 0 101 301\""));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            svg("/dev/input/event0: EV_KEY KEY_A DOWN\n", &BTreeMap::new()).unwrap_err(),
+            "no touch contacts in the input"
+        );
+        let input = INPUT
+            .replace("KEY_POWER", "BTN_TOUCH")
+            .replace("event0", "event1");
+        assert_eq!(
+            svg(&input, &BTreeMap::new()).unwrap_err(),
+            "contacts on /dev/input/event1 and /dev/input/event2, select one device with \
+             `--include device=<DEVICE>`"
+        );
+    }
+}
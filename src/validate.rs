@@ -0,0 +1,291 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use crate::device::DeviceInfo;
+use crate::linux::input_event_codes::*;
+use crate::mt::MtState;
+use crate::{gen, is_syn_report, Error, InputEvent, ParseResult};
+
+/// A semantic problem in a recording.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// Line of the event that the problem was found at.
+    pub line: usize,
+    /// Device of the packet, unless the line could not be parsed.
+    pub device: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        if let Some(device) = &self.device {
+            write!(f, "{}: ", device)?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// State of one device while validating.
+#[derive(Debug, Default)]
+struct DeviceState {
+    /// Keys that are down, with the line that pressed them.
+    keys: BTreeMap<u16, usize>,
+    mt: MtState,
+    /// Whether the events are discarded until the next `SYN_REPORT` after `SYN_DROPPED`.
+    dropped: bool,
+    /// Slots without a contact whose events have been reported already.
+    reported: BTreeSet<i32>,
+}
+
+impl DeviceState {
+    fn event(&mut self, event: &InputEvent, line: usize, problems: &mut Vec<String>) {
+        self.mt.event(event.r#type, event.code, event.value);
+        let name = code_name(event.r#type, event.code);
+        let slot = self.mt.slot();
+        match (i32::from(event.r#type), i32::from(event.code)) {
+            (EV_SYN, SYN_DROPPED) => {
+                self.dropped = true;
+                problems.push("SYN_DROPPED: the kernel dropped events here".to_string());
+            }
+            (EV_SYN, SYN_REPORT) => self.dropped = false,
+            (EV_KEY, _) => match event.value {
+                0 if self.keys.remove(&event.code).is_none() => {
+                    problems.push(format!("{} released without being pressed", name));
+                }
+                1 if self.keys.contains_key(&event.code) => {
+                    problems.push(format!("{} pressed again without being released", name));
+                }
+                1 => {
+                    self.keys.insert(event.code, line);
+                }
+                _ => {}
+            },
+            (EV_ABS, ABS_MT_TRACKING_ID) if event.value != -1 => {
+                self.reported.remove(&slot);
+            }
+            (EV_ABS, ABS_MT_SLOT | ABS_MT_TRACKING_ID) => {}
+            (EV_ABS, ABS_MT_TOUCH_MAJOR..=ABS_MAX)
+                if self.mt.is_multitouch()
+                    && !self.dropped
+                    && !self.mt.is_touching(slot)
+                    && self.reported.insert(slot) =>
+            {
+                problems.push(format!(
+                    "{} in slot {}, which has no tracking ID",
+                    name, slot
+                ));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Checks an event against the capabilities of its device.
+fn check_capabilities(event: &InputEvent, info: &DeviceInfo, problems: &mut Vec<String>) {
+    let name = code_name(event.r#type, event.code);
+    match i32::from(event.r#type) {
+        EV_KEY if !info.keys.contains(&event.code) => {
+            problems.push(format!("{} is not supported by the device", name));
+        }
+        EV_ABS => match info.abs.get(&event.code) {
+            None => problems.push(format!("{} is not supported by the device", name)),
+            // A tracking ID of -1 lifts the contact.
+            Some(_) if i32::from(event.code) == ABS_MT_TRACKING_ID && event.value == -1 => {}
+            Some(absinfo) if !(absinfo.minimum..=absinfo.maximum).contains(&event.value) => {
+                problems.push(format!(
+                    "{} value {} is outside the range {}..={} of the device",
+                    name, event.value, absinfo.minimum, absinfo.maximum
+                ));
+            }
+            Some(_) => {}
+        },
+        _ => {}
+    }
+}
+
+fn code_name(r#type: u16, code: u16) -> String {
+    gen::get_code_name(r#type, code)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{:04x}", code))
+}
+
+/// Finds semantic problems in the parsed lines: keys pressed without being released and the
+/// other way around, events of multitouch slots without a tracking ID, packets that are not
+/// terminated by `SYN_REPORT`, types and codes without a name, `SYN_DROPPED` and, for devices
+/// in `device_info`, events that the device does not support or whose values are outside the
+/// ranges of its axes. Lines that cannot be parsed are problems too. Events without a device
+/// belong to `device`.
+pub(crate) fn validate(
+    lines: impl Iterator<Item = (usize, ParseResult)>,
+    device: Option<&str>,
+    device_info: &BTreeMap<String, DeviceInfo>,
+) -> Result<Vec<Problem>, Error> {
+    let mut devices: HashMap<String, DeviceState> = HashMap::new();
+    // Line of the last event of each device whose packet has not been terminated yet.
+    let mut unterminated: BTreeMap<String, usize> = BTreeMap::new();
+    let mut problems = vec![];
+
+    for (line, result) in lines {
+        let (event_device, event) = match result {
+            Ok(result) => result,
+            Err(Error::IO(error)) => return Err(Error::IO(error)),
+            Err(error) => {
+                problems.push(Problem {
+                    line,
+                    device: None,
+                    message: error.to_string(),
+                });
+                continue;
+            }
+        };
+        let event_device = match event_device.or_else(|| device.map(str::to_string)) {
+            Some(device) => device,
+            None => {
+                problems.push(Problem {
+                    line,
+                    device: None,
+                    message: "missing device field and no default device".to_string(),
+                });
+                continue;
+            }
+        };
+        let state = devices.entry(event_device.clone()).or_default();
+        let mut messages = vec![];
+        if gen::get_type_name(event.r#type).is_none() {
+            messages.push(format!("unknown event type {:04x}", event.r#type));
+        } else if gen::get_code_name(event.r#type, event.code).is_none()
+            && i32::from(event.r#type) != EV_PWR
+        {
+            messages.push(format!(
+                "unknown code {:04x} for {}",
+                event.code,
+                gen::get_type_name(event.r#type).unwrap()
+            ));
+        } else if let Some(info) = device_info.get(&event_device) {
+            check_capabilities(&event, info, &mut messages);
+        }
+        state.event(&event, line, &mut messages);
+        problems.extend(messages.into_iter().map(|message| Problem {
+            line,
+            device: Some(event_device.clone()),
+            message,
+        }));
+        if is_syn_report(&event) {
+            unterminated.remove(&event_device);
+        } else {
+            unterminated.insert(event_device, line);
+        }
+    }
+
+    for (device, line) in unterminated {
+        problems.push(Problem {
+            line,
+            device: Some(device),
+            message: "packet not terminated by SYN_REPORT".to_string(),
+        });
+    }
+    for (device, state) in devices {
+        for (code, line) in state.keys {
+            problems.push(Problem {
+                line,
+                device: Some(device.clone()),
+                message: format!(
+                    "{} pressed and never released",
+                    code_name(EV_KEY as u16, code)
+                ),
+            });
+        }
+    }
+    problems.sort_by_key(|problem| problem.line);
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate;
+    use crate::device::DeviceInfo;
+    use crate::parse_all;
+    use std::collections::BTreeMap;
+
+    const DUMP: &str = "\
+add device 2: /dev/input/event2
+  events:
+    KEY (0001): BTN_TOUCH
+    ABS (0003): ABS_MT_SLOT           : value 0, min 0, max 9, fuzz 0, flat 0, resolution 0
+                ABS_MT_POSITION_X     : value 0, min 0, max 1079, fuzz 0, flat 0, resolution 0
+                ABS_MT_TRACKING_ID    : value 0, min 0, max 65535, fuzz 0, flat 0, resolution 0
+";
+
+    fn problems(input: &str, device_info: &BTreeMap<String, DeviceInfo>) -> Vec<String> {
+        validate(parse_all(&mut input.as_bytes()), None, device_info)
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn keys_and_packets() {
+        let input = "\
+/dev/input/event0: EV_KEY KEY_A DOWN
+/dev/input/event0: EV_SYN SYN_REPORT 00000000
+/dev/input/event0: EV_KEY KEY_A DOWN
+/dev/input/event0: EV_KEY KEY_B UP
+/dev/input/event0: EV_SYN SYN_REPORT 00000000
+/dev/input/event0: EV_KEY 02ff DOWN
+/dev/input/event0: 001e 0000 00000000
+/dev/input/event0: EV_SYN SYN_DROPPED 00000000
+/dev/input/event0: EV_SYN SYN_REPORT 00000000
+/dev/input/event0: EV_KEY KEY_C
+/dev/input/event0: EV_KEY 02ff UP
+";
+        assert_eq!(
+            problems(input, &BTreeMap::new()),
+            [
+                "line 1: /dev/input/event0: KEY_A pressed and never released",
+                "line 3: /dev/input/event0: KEY_A pressed again without being released",
+                "line 4: /dev/input/event0: KEY_B released without being pressed",
+                "line 6: /dev/input/event0: unknown code 02ff for EV_KEY",
+                "line 7: /dev/input/event0: unknown event type 001e",
+                "line 8: /dev/input/event0: SYN_DROPPED: the kernel dropped events here",
+                "line 10: missing value field for line: \"/dev/input/event0: EV_KEY KEY_C\"",
+                "line 11: /dev/input/event0: unknown code 02ff for EV_KEY",
+                "line 11: /dev/input/event0: packet not terminated by SYN_REPORT",
+            ]
+        );
+    }
+
+    #[test]
+    fn touches() {
+        let input = "\
+/dev/input/event2: EV_ABS ABS_MT_SLOT 00000000
+/dev/input/event2: EV_ABS ABS_MT_TRACKING_ID 00000001
+/dev/input/event2: EV_ABS ABS_MT_POSITION_X 00000500
+/dev/input/event2: EV_KEY BTN_TOUCH DOWN
+/dev/input/event2: EV_SYN SYN_REPORT 00000000
+/dev/input/event2: EV_ABS ABS_MT_SLOT 00000001
+/dev/input/event2: EV_ABS ABS_MT_POSITION_X 00000010
+/dev/input/event2: EV_ABS ABS_MT_POSITION_Y 00000010
+/dev/input/event2: EV_SYN SYN_REPORT 00000000
+/dev/input/event2: EV_ABS ABS_MT_SLOT 00000000
+/dev/input/event2: EV_ABS ABS_MT_TRACKING_ID ffffffff
+/dev/input/event2: EV_KEY BTN_TOUCH UP
+/dev/input/event2: EV_SYN SYN_REPORT 00000000
+";
+        let device_info = DeviceInfo::all_from_getevent(&mut DUMP.as_bytes()).unwrap();
+        assert_eq!(
+            problems(input, &device_info),
+            [
+                "line 3: /dev/input/event2: ABS_MT_POSITION_X value 1280 is outside the range \
+                 0..=1079 of the device",
+                "line 7: /dev/input/event2: ABS_MT_POSITION_X in slot 1, which has no tracking ID",
+                "line 8: /dev/input/event2: ABS_MT_POSITION_Y is not supported by the device",
+            ]
+        );
+        assert_eq!(
+            problems(input, &BTreeMap::new()),
+            ["line 7: /dev/input/event2: ABS_MT_POSITION_X in slot 1, which has no tracking ID"]
+        );
+    }
+}
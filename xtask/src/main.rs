@@ -0,0 +1,341 @@
+//! Development tasks, run with `cargo xtask <TASK>`.
+//!
+//! `codegen` generates the constants in `src/linux` and the tables of names in `src/gen` from
+//! the kernel headers vendored in `xtask/include`. To pick up new codes, copy newer headers
+//! there and run it again.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const INPUT_EVENT_CODES_H: &str = include_str!("../include/linux/input-event-codes.h");
+const INPUT_H: &str = include_str!("../include/linux/input.h");
+
+/// Line of `src/linux/input.rs` after which the generated constants start.
+const INPUT_RS_MARKER: &str =
+    "// Generated by `cargo xtask codegen` from input.h, do not edit below.";
+
+const HEADER: &str = "// Generated by `cargo xtask codegen`, do not edit.";
+
+/// A table of names, from which `get_<name>_value` and `get_<name>_name` are generated.
+struct Table {
+    name: &'static str,
+    prefixes: &'static [&'static str],
+}
+
+/// Tables in the order in which defines are assigned to them, so `FF_STATUS_*` are not `FF_*`.
+const TABLES: &[Table] = &[
+    Table {
+        name: "input_prop",
+        prefixes: &["INPUT_PROP_"],
+    },
+    Table {
+        name: "ev",
+        prefixes: &["EV_"],
+    },
+    Table {
+        name: "syn",
+        prefixes: &["SYN_"],
+    },
+    Table {
+        name: "key",
+        prefixes: &["KEY_", "BTN_"],
+    },
+    Table {
+        name: "rel",
+        prefixes: &["REL_"],
+    },
+    Table {
+        name: "abs",
+        prefixes: &["ABS_"],
+    },
+    Table {
+        name: "sw",
+        prefixes: &["SW_"],
+    },
+    Table {
+        name: "msc",
+        prefixes: &["MSC_"],
+    },
+    Table {
+        name: "led",
+        prefixes: &["LED_"],
+    },
+    Table {
+        name: "rep",
+        prefixes: &["REP_"],
+    },
+    Table {
+        name: "snd",
+        prefixes: &["SND_"],
+    },
+    Table {
+        name: "mt_tool",
+        prefixes: &["MT_TOOL_"],
+    },
+    Table {
+        name: "ff_status",
+        prefixes: &["FF_STATUS_"],
+    },
+    Table {
+        name: "ff",
+        prefixes: &["FF_"],
+    },
+];
+
+/// Prefixes of the defines of input.h that are generated, besides the ones of `EV_VERSION`.
+const INPUT_H_PREFIXES: &[&str] = &["EV_", "MT_TOOL_", "FF_"];
+
+/// A `#define` of an integer constant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Define {
+    name: String,
+    /// Rust expression of the value, such as `0x2ff`, `KEY_COFFEE` or `KEY_MAX + 1`.
+    value: String,
+    /// Module of `src/linux` that has the constant.
+    module: &'static str,
+}
+
+impl Define {
+    /// Value of a define that is given as a number, rather than as another define.
+    fn literal(&self) -> Option<i64> {
+        match self.value.strip_prefix("0x") {
+            Some(digits) => i64::from_str_radix(digits, 16).ok(),
+            None => self.value.parse().ok(),
+        }
+    }
+
+    fn table(&self) -> Option<&'static Table> {
+        if self.name == "EV_VERSION" || self.name.ends_with("_CNT") {
+            return None;
+        }
+        TABLES.iter().find(|table| {
+            table
+                .prefixes
+                .iter()
+                .any(|prefix| self.name.starts_with(prefix))
+        })
+    }
+}
+
+/// Translates a C expression like `(KEY_MAX+1)` to Rust.
+fn expression(value: &str) -> Result<String, String> {
+    let value: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    let value = value
+        .strip_prefix('(')
+        .and_then(|value| value.strip_suffix(')'))
+        .unwrap_or(&value);
+    let terms: Vec<_> = value.split('+').collect();
+    for term in &terms {
+        if term.is_empty() || !term.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("unsupported expression {:?}", value));
+        }
+    }
+    Ok(terms.join(" + "))
+}
+
+/// Parses the object-like `#define`s of integer constants of a header, skipping include guards.
+fn parse(header: &str, module: &'static str) -> Result<Vec<Define>, String> {
+    let mut defines = vec![];
+    for (index, line) in header.lines().enumerate() {
+        let rest = match line.strip_prefix("#define") {
+            Some(rest) if rest.starts_with(char::is_whitespace) => rest.trim_start(),
+            _ => continue,
+        };
+        let (name, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let value = value.split("/*").next().unwrap().trim();
+        if name.starts_with('_') || name.contains('(') || value.is_empty() {
+            continue;
+        }
+        if module == "input"
+            && !INPUT_H_PREFIXES
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+        {
+            continue;
+        }
+        defines.push(Define {
+            name: name.to_string(),
+            value: expression(value).map_err(|error| format!("line {}: {}", index + 1, error))?,
+            module,
+        });
+    }
+    Ok(defines)
+}
+
+fn constants(defines: &[Define]) -> String {
+    let mut output = String::new();
+    for define in defines {
+        writeln!(output, "pub const {}: i32 = {};", define.name, define.value).unwrap();
+    }
+    output
+}
+
+/// Functions from names to values and back for each table. Names of the forward tables include
+/// aliases and `*_MAX`, while the reverse tables have the last name defined for each value.
+fn tables(defines: &[Define]) -> String {
+    let mut output = format!(
+        "{}\n\nuse crate::linux::{{input, input_event_codes}};\n",
+        HEADER
+    );
+    for table in TABLES {
+        let defines: Vec<_> = defines
+            .iter()
+            .filter(|define| define.table().is_some_and(|t| t.name == table.name))
+            .collect();
+        let mut names = HashMap::new();
+        for define in &defines {
+            if let Some(value) = define.literal().filter(|_| !define.name.ends_with("_MAX")) {
+                names.insert(value, define.name.as_str());
+            }
+        }
+
+        write!(
+            output,
+            "\npub(super) fn get_{}_value(name: &str) -> Option<i32> {{\n    Some(match name {{\n",
+            table.name
+        )
+        .unwrap();
+        for define in &defines {
+            writeln!(
+                output,
+                "        \"{}\" => {}::{},",
+                define.name, define.module, define.name
+            )
+            .unwrap();
+        }
+        output.push_str("        _ => return None,\n    })\n}\n");
+
+        write!(
+            output,
+            "\npub(super) fn get_{}_name(value: i32) -> Option<&'static str> {{\n    \
+             Some(match value {{\n",
+            table.name
+        )
+        .unwrap();
+        for define in &defines {
+            if define
+                .literal()
+                .is_some_and(|value| names.get(&value) == Some(&define.name.as_str()))
+            {
+                writeln!(
+                    output,
+                    "        {}::{} => \"{}\",",
+                    define.module, define.name, define.name
+                )
+                .unwrap();
+            }
+        }
+        output.push_str("        _ => return None,\n    })\n}\n");
+    }
+    output
+}
+
+/// Generated files, relative to the root of the repository, with their contents.
+fn generate(root: &Path) -> Result<Vec<(PathBuf, String)>, String> {
+    let codes = parse(INPUT_EVENT_CODES_H, "input_event_codes")
+        .map_err(|error| format!("input-event-codes.h: {}", error))?;
+    let input = parse(INPUT_H, "input").map_err(|error| format!("input.h: {}", error))?;
+
+    let input_rs = root.join("src/linux/input.rs");
+    let existing = fs::read_to_string(&input_rs)
+        .map_err(|error| format!("{}: {}", input_rs.display(), error))?;
+    let (handwritten, _) = existing
+        .split_once(INPUT_RS_MARKER)
+        .ok_or_else(|| format!("{}: missing {:?}", input_rs.display(), INPUT_RS_MARKER))?;
+
+    Ok(vec![
+        (
+            PathBuf::from("src/linux/input_event_codes.rs"),
+            format!("{}\n\n{}", HEADER, constants(&codes)),
+        ),
+        (
+            PathBuf::from("src/linux/input.rs"),
+            format!(
+                "{}{}\n\n{}",
+                handwritten,
+                INPUT_RS_MARKER,
+                constants(&input)
+            ),
+        ),
+        (
+            PathBuf::from("src/gen/codes.rs"),
+            tables(&[codes, input].concat()),
+        ),
+    ])
+}
+
+fn root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf()
+}
+
+fn codegen() -> Result<(), String> {
+    let root = root();
+    for (path, contents) in generate(&root)? {
+        fs::write(root.join(&path), contents)
+            .map_err(|error| format!("{}: {}", path.display(), error))?;
+        println!("wrote {}", path.display());
+    }
+    Ok(())
+}
+
+fn main() {
+    let result = match std::env::args().nth(1).as_deref() {
+        Some("codegen") => codegen(),
+        _ => Err("usage: cargo xtask codegen".to_string()),
+    };
+    if let Err(error) = result {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expression, generate, parse, root, Define};
+    use std::fs;
+
+    #[test]
+    fn parse_defines() {
+        let header = "\
+#ifndef _INPUT_EVENT_CODES_H
+#define _INPUT_EVENT_CODES_H
+#define KEY_COFFEE		152	/* AL Terminal Lock/Screensaver */
+#define KEY_SCREENLOCK		KEY_COFFEE
+#define KEY_CNT			(KEY_MAX+1)
+#define EVIOCGBIT(ev,len)	_IOC(_IOC_READ, 'E', 0x20 + (ev), len)
+";
+        let define = |name: &str, value: &str| Define {
+            name: name.to_string(),
+            value: value.to_string(),
+            module: "input_event_codes",
+        };
+        assert_eq!(
+            parse(header, "input_event_codes").unwrap(),
+            [
+                define("KEY_COFFEE", "152"),
+                define("KEY_SCREENLOCK", "KEY_COFFEE"),
+                define("KEY_CNT", "KEY_MAX + 1"),
+            ]
+        );
+        assert!(expression("(1 << 0)").is_err());
+    }
+
+    /// Fails if the headers or the generator changed without running `cargo xtask codegen`.
+    #[test]
+    fn generated_files_are_up_to_date() {
+        let root = root();
+        for (path, contents) in generate(&root).unwrap() {
+            assert!(
+                fs::read_to_string(root.join(&path)).unwrap() == contents,
+                "{} is out of date, run `cargo xtask codegen`",
+                path.display()
+            );
+        }
+    }
+}